@@ -2,6 +2,7 @@ use crate::api::gw2::fetch_map_names_thread;
 use crate::config::{config_dir, migrate_configs, Config};
 use crate::context::{init_context, Context};
 use crate::thread::background_thread;
+use crate::util::reshade::transition::restore_transition_duration;
 use function_name::named;
 use log::info;
 use nexus::gui::{register_render, RenderType};
@@ -78,6 +79,8 @@ impl Addon {
             function_name!()
         );
         Self::unload_threads();
+        Self::lock().context.reshade.ipc.disconnect();
+        restore_transition_duration();
         Self::save_config();
         info!("[{}] reshade_preset_switcher unloaded", function_name!());
     }
@@ -0,0 +1,29 @@
+use crate::context::game_state::GameState;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GameStatePresets {
+    #[serde(default)]
+    pub character_select: Option<PathBuf>,
+    #[serde(default)]
+    pub loading_screen: Option<PathBuf>,
+}
+
+impl GameStatePresets {
+    pub fn get(&self, game_state: &GameState) -> Option<&PathBuf> {
+        match game_state {
+            GameState::InGame => None,
+            GameState::LoadingScreen => self.loading_screen.as_ref(),
+            GameState::CharacterSelect => self.character_select.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, game_state: &GameState) -> Option<&mut Option<PathBuf>> {
+        match game_state {
+            GameState::InGame => None,
+            GameState::LoadingScreen => Some(&mut self.loading_screen),
+            GameState::CharacterSelect => Some(&mut self.character_select),
+        }
+    }
+}
@@ -1,14 +1,22 @@
+pub mod game_state_presets;
 pub mod preset_rule;
 pub mod reshade_config;
+pub mod time_period_config;
 
 use crate::addon::{Addon, VERSION};
+use crate::config::game_state_presets::GameStatePresets;
 use crate::config::preset_rule::PresetRule;
 pub use crate::config::reshade_config::ReshadeConfig;
+use crate::config::time_period_config::TimePeriodConfig;
+use crate::render::util::ui::UiElement;
 use function_name::named;
-use log::info;
+use log::{info, warn};
 use nexus::paths::{get_addon_dir, get_game_dir};
 use semver::Version;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
@@ -20,6 +28,10 @@ pub struct Config {
     pub version: String,
     pub preset_rules: Vec<PresetRule>,
     pub reshade: ReshadeConfig,
+    #[serde(default)]
+    pub game_state_presets: GameStatePresets,
+    #[serde(default)]
+    pub time_period: TimePeriodConfig,
 }
 
 impl Default for Config {
@@ -28,6 +40,8 @@ impl Default for Config {
             version: VERSION.to_string(),
             preset_rules: Vec::new(),
             reshade: ReshadeConfig::default(),
+            game_state_presets: GameStatePresets::default(),
+            time_period: TimePeriodConfig::default(),
         }
     }
 }
@@ -40,9 +54,10 @@ impl Config {
             .inspect_err(|err| log::warn!("Failed to read config: {err}"))
             .ok()?;
         let reader = BufReader::new(file);
-        let config = serde_json::from_reader(reader)
+        let mut config: Config = serde_json::from_reader(reader)
             .inspect_err(|err| log::warn!("Failed to parse config: {err}"))
             .ok()?;
+        config.renew_duplicate_rule_ids();
         info!(
             "[{}] Loaded config from \"{}\"",
             function_name!(),
@@ -72,6 +87,16 @@ impl Config {
         config_dir().join("config.json")
     }
 
+    // Ids copied by hand would make rules share their variant.
+    fn renew_duplicate_rule_ids(&mut self) {
+        let mut ids = HashSet::new();
+        for rule in self.preset_rules.iter_mut() {
+            while !ids.insert(rule.id.clone()) {
+                rule.renew_id();
+            }
+        }
+    }
+
     pub fn valid(&self) -> bool {
         self.reshade.ini_path.exists()
     }
@@ -85,6 +110,33 @@ pub fn game_dir() -> PathBuf {
     get_game_dir().expect("invalid game directory")
 }
 
+// Data files are created from bundled defaults and may be edited by users afterwards.
+#[named]
+pub fn load_data_file<T: DeserializeOwned>(file_name: &str, default_content: &str) -> T {
+    let path = config_dir().join(file_name);
+    if !path.exists() {
+        match fs::write(&path, default_content) {
+            Ok(_) => info!(
+                "[{}] Created \"{}\" from defaults",
+                function_name!(),
+                path.display()
+            ),
+            Err(err) => warn!("Failed to create {file_name}: {err}"),
+        }
+    }
+    File::open(&path)
+        .inspect_err(|err| warn!("Failed to read {file_name}: {err}"))
+        .ok()
+        .and_then(|file| {
+            serde_json::from_reader(BufReader::new(file))
+                .inspect_err(|err| warn!("Failed to parse {file_name}: {err}"))
+                .ok()
+        })
+        .unwrap_or_else(|| {
+            serde_json::from_str(default_content).expect("invalid default data file")
+        })
+}
+
 fn default_version() -> String {
     VERSION.to_string()
 }
@@ -1,25 +1,37 @@
 pub mod rule_condition;
+pub mod uniform_override;
 
 use crate::config::preset_rule::rule_condition::condition_data::ConditionData;
 use crate::config::preset_rule::rule_condition::conjunction_type::ConjunctionType;
 use crate::config::preset_rule::rule_condition::RuleCondition;
+use crate::config::preset_rule::uniform_override::UniformOverride;
 use crate::context::reshade_context::ReshadeContext;
-use crate::context::time_period::CurrentTimePeriod;
+use crate::context::time_period::EvaluationTime;
 use crate::context::Context;
 use crate::render::util::ui::UiElement;
-use crate::util::reshade::switch_to_preset;
+use crate::util::reshade::managed_preset::{managed_preset_path, write_managed_preset};
 use function_name::named;
-use log::debug;
-use rand::Rng;
+use log::{debug, error};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetRule {
+    // Names generated files, so renaming a rule keeps its variant and keybind.
+    #[serde(default = "new_rule_id")]
+    pub id: String,
     pub rule_name: String,
     pub preset_path: PathBuf,
     #[serde(default)]
     pub conditions: Vec<RuleCondition>,
+    #[serde(default)]
+    pub transition_duration_ms: Option<u32>,
+    #[serde(default)]
+    pub technique_toggles: BTreeMap<String, bool>,
+    #[serde(default)]
+    pub uniform_overrides: Vec<UniformOverride>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,9 +48,13 @@ pub struct RuleProcessingResult {
 impl Default for PresetRule {
     fn default() -> Self {
         Self {
+            id: new_rule_id(),
             rule_name: "Rule".to_string(),
             preset_path: Default::default(),
             conditions: Vec::new(),
+            transition_duration_ms: None,
+            technique_toggles: BTreeMap::new(),
+            uniform_overrides: Vec::new(),
         }
     }
 }
@@ -51,49 +67,85 @@ impl UiElement for PresetRule {
     fn name(&self) -> &String {
         &self.rule_name
     }
+
+    fn renew_id(&mut self) {
+        self.id = new_rule_id();
+    }
+}
+
+pub fn new_rule_id() -> String {
+    format!("{:08x}", rand::random::<u32>())
 }
 
 impl PresetRule {
-    pub fn validate(&self) -> Result<(), RuleValidationError> {
-        if !self.preset_path.exists() {
+    pub fn validate(&self, reshade_context: &ReshadeContext) -> Result<(), RuleValidationError> {
+        if !reshade_context.preset_exists(&self.preset_path) {
             return Err(RuleValidationError::NoPresetSelected);
         }
         Ok(())
     }
 
+    // Outcome without side effects, as used by the forecast: chance conditions are
+    // unknown (None) instead of rolled and the preset file is not checked.
+    pub fn predict(
+        &self,
+        context: &Context,
+        current_map_id: &u32,
+        evaluation_time: &EvaluationTime,
+    ) -> Option<bool> {
+        let mut rule_fulfilled = Some(false);
+        let mut and_chain_fulfilled = Some(true);
+        for (i, rule_condition) in self.conditions.iter().enumerate() {
+            if i > 0 && matches!(rule_condition.conjunction_type, ConjunctionType::Or) {
+                rule_fulfilled = or(rule_fulfilled, and_chain_fulfilled);
+                and_chain_fulfilled = Some(true);
+            }
+            let condition_fulfilled = match rule_condition.data {
+                ConditionData::Chance(_) => None,
+                _ => Some(self.condition_fulfilled(
+                    i,
+                    &rule_condition.data,
+                    context,
+                    current_map_id,
+                    evaluation_time,
+                )),
+            };
+            and_chain_fulfilled = and(and_chain_fulfilled, condition_fulfilled);
+        }
+        if self.conditions.is_empty() {
+            return Some(false);
+        }
+        or(rule_fulfilled, and_chain_fulfilled)
+    }
+
     #[named]
-    pub fn evaluate(&self, context: &Context, current_map_id: &u32) -> RuleProcessingResult {
-        let validation_result = self.validate();
+    pub fn evaluate(
+        &self,
+        context: &Context,
+        current_map_id: &u32,
+        evaluation_time: &EvaluationTime,
+    ) -> RuleProcessingResult {
+        let validation_result = self.validate(&context.reshade);
         if validation_result.is_ok() {
             let mut rule_fulfilled = false;
             let mut inside_failed_and_chain = false;
-            let rule_condition_iter = &mut self.conditions.iter().peekable();
-            while let Some(rule_condition) = rule_condition_iter.next() {
+            let rule_condition_iter = &mut self.conditions.iter().enumerate().peekable();
+            while let Some((condition_index, rule_condition)) = rule_condition_iter.next() {
                 let current_condition_fulfilled = {
                     if inside_failed_and_chain {
                         false
                     } else {
-                        match &rule_condition.data {
-                            ConditionData::Maps(maps) => maps.contains(current_map_id),
-                            ConditionData::BlacklistedMaps(maps) => !maps.contains(current_map_id),
-                            ConditionData::Time(time_periods) => {
-                                match context.current_time_period {
-                                    CurrentTimePeriod::Day => time_periods.day,
-                                    CurrentTimePeriod::Dusk => time_periods.dusk,
-                                    CurrentTimePeriod::Night => time_periods.night,
-                                    CurrentTimePeriod::Dawn => time_periods.dawn,
-                                }
-                            }
-                            ConditionData::Chance(chance) => {
-                                let mut gen = rand::thread_rng();
-                                let roll = gen.gen_range(0.0..=1.0);
-                                roll <= *chance
-                            }
-                        }
+                        self.condition_fulfilled(
+                            condition_index,
+                            &rule_condition.data,
+                            context,
+                            current_map_id,
+                            evaluation_time,
+                        )
                     }
                 };
 
-                match rule_condition_iter.peek() {
+                match rule_condition_iter.peek().map(|(_, next)| next) {
                     None => {
                         //never true if inside_failed_and_chain
                         rule_fulfilled = current_condition_fulfilled;
@@ -140,7 +192,103 @@ impl PresetRule {
         }
     }
 
-    pub fn activate(&self, reshade_context: &ReshadeContext) {
-        switch_to_preset(&self.preset_path, reshade_context);
+    pub fn polled_condition_results(&self, context: &Context, current_map_id: &u32) -> Vec<bool> {
+        let evaluation_time = context.evaluation_time_now();
+        self.conditions
+            .iter()
+            .enumerate()
+            .filter(|(_, rule_condition)| rule_condition.data.is_polled())
+            .map(|(condition_index, rule_condition)| {
+                self.condition_fulfilled(
+                    condition_index,
+                    &rule_condition.data,
+                    context,
+                    current_map_id,
+                    &evaluation_time,
+                )
+            })
+            .collect()
+    }
+
+    // Chance rolls and speed matches are kept in the context between evaluations.
+    fn condition_fulfilled(
+        &self,
+        condition_index: usize,
+        condition_data: &ConditionData,
+        context: &Context,
+        current_map_id: &u32,
+        evaluation_time: &EvaluationTime,
+    ) -> bool {
+        match condition_data {
+            ConditionData::Chance(chance) => {
+                context
+                    .condition_state
+                    .chance_fulfilled(&self.id, condition_index, *chance)
+            }
+            ConditionData::Speed(_) => context
+                .condition_state
+                .speed_fulfilled(&self.id, condition_index),
+            _ => condition_data.is_fulfilled(context, current_map_id, evaluation_time),
+        }
+    }
+
+    // Rules changing techniques or uniforms switch to a generated variant of their preset.
+    pub fn has_variant(&self) -> bool {
+        !self.technique_toggles.is_empty() || !self.uniform_overrides.is_empty()
+    }
+
+    pub fn activation_preset_path(&self, reshade_context: &ReshadeContext) -> PathBuf {
+        let preset_path = reshade_context.resolve(&self.preset_path);
+        if self.has_variant() {
+            managed_preset_path(&preset_path, &self.id)
+        } else {
+            preset_path
+        }
+    }
+
+    pub fn write_variant(&self, reshade_context: &ReshadeContext) -> io::Result<PathBuf> {
+        let variant_path = self.activation_preset_path(reshade_context);
+        write_managed_preset(
+            &reshade_context.resolve(&self.preset_path),
+            &variant_path,
+            &self.technique_toggles,
+            &self.uniform_overrides,
+        )?;
+        Ok(variant_path)
+    }
+
+    #[named]
+    // Path of the preset to switch to, the base preset is used when the variant cannot be written.
+    pub fn prepare_activation(&self, reshade_context: &ReshadeContext) -> PathBuf {
+        let mut preset_path = reshade_context.resolve(&self.preset_path);
+        if self.has_variant() {
+            match self.write_variant(reshade_context) {
+                Ok(variant_path) => preset_path = variant_path,
+                Err(e) => error!(
+                    "[{}] Could not write variant of preset [{}], using base preset: {}",
+                    function_name!(),
+                    preset_path.display(),
+                    e
+                ),
+            }
+        }
+        preset_path
+    }
+}
+
+// Three-valued logic, None is unknown.
+fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
     }
 }
@@ -0,0 +1,143 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DateRange {
+    pub from: MonthDay,
+    pub to: MonthDay,
+    // Ranges without a year recur every year.
+    #[serde(default)]
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Default for DateRange {
+    fn default() -> DateRange {
+        Self {
+            from: MonthDay { month: 1, day: 1 },
+            to: MonthDay { month: 12, day: 31 },
+            year: None,
+        }
+    }
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let month_day = MonthDay::from(date);
+        if self.from <= self.to {
+            self.from <= month_day && month_day <= self.to && self.matches_year(date.year())
+        } else {
+            (month_day >= self.from && self.matches_year(date.year()))
+                || (month_day <= self.to && self.matches_year(date.year() - 1))
+        }
+    }
+
+    fn matches_year(&self, start_year: i32) -> bool {
+        self.year.is_none() || self.year == Some(start_year)
+    }
+}
+
+impl MonthDay {
+    // February allows the 29th, as ranges recur in leap years too.
+    pub fn days_in_month(month: u32) -> u32 {
+        match month {
+            2 => 29,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    pub fn clamp_day(&mut self) {
+        self.day = self.day.clamp(1, Self::days_in_month(self.month));
+    }
+}
+
+impl From<NaiveDate> for MonthDay {
+    fn from(date: NaiveDate) -> Self {
+        Self {
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn range(from: (u32, u32), to: (u32, u32), year: Option<i32>) -> DateRange {
+        DateRange {
+            from: MonthDay {
+                month: from.0,
+                day: from.1,
+            },
+            to: MonthDay {
+                month: to.0,
+                day: to.1,
+            },
+            year,
+        }
+    }
+
+    #[test]
+    fn includes_both_ends() {
+        let halloween = range((10, 15), (11, 5), None);
+        assert!(!halloween.contains(date(2024, 10, 14)));
+        assert!(halloween.contains(date(2024, 10, 15)));
+        assert!(halloween.contains(date(2024, 10, 31)));
+        assert!(halloween.contains(date(2024, 11, 5)));
+        assert!(!halloween.contains(date(2024, 11, 6)));
+        assert!(range((3, 1), (3, 1), None).contains(date(2023, 3, 1)));
+    }
+
+    #[test]
+    fn wraps_from_december_to_january() {
+        let wintersday = range((12, 10), (1, 5), None);
+        assert!(!wintersday.contains(date(2024, 12, 9)));
+        assert!(wintersday.contains(date(2024, 12, 10)));
+        assert!(wintersday.contains(date(2024, 12, 31)));
+        assert!(wintersday.contains(date(2025, 1, 1)));
+        assert!(wintersday.contains(date(2025, 1, 5)));
+        assert!(!wintersday.contains(date(2025, 1, 6)));
+        assert!(!wintersday.contains(date(2025, 6, 1)));
+    }
+
+    #[test]
+    fn year_applies_to_start_of_range() {
+        let wintersday = range((12, 10), (1, 5), Some(2024));
+        assert!(wintersday.contains(date(2024, 12, 20)));
+        assert!(wintersday.contains(date(2025, 1, 2)));
+        assert!(!wintersday.contains(date(2024, 1, 2)));
+        assert!(!wintersday.contains(date(2025, 12, 20)));
+        let festival = range((6, 1), (6, 30), Some(2024));
+        assert!(festival.contains(date(2024, 6, 15)));
+        assert!(!festival.contains(date(2025, 6, 15)));
+    }
+
+    #[test]
+    fn leap_day() {
+        let february = range((2, 1), (2, 29), None);
+        assert!(february.contains(date(2024, 2, 29)));
+        assert!(february.contains(date(2023, 2, 28)));
+        assert!(!february.contains(date(2023, 3, 1)));
+    }
+
+    #[test]
+    fn clamps_day_to_month() {
+        let cases = [((2, 31), 29), ((4, 31), 30), ((12, 31), 31), ((1, 0), 1)];
+        for ((month, day), expected) in cases {
+            let mut month_day = MonthDay { month, day };
+            month_day.clamp_day();
+            assert_eq!(month_day.day, expected, "{}-{}", month, day);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::config::preset_rule::rule_condition::condition_data::is_minute_in_range;
+use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LocalTimeCondition {
+    pub from: u32,
+    pub to: u32,
+    #[serde(default)]
+    pub weekdays: Weekdays,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Weekdays {
+    pub monday: bool,
+    pub tuesday: bool,
+    pub wednesday: bool,
+    pub thursday: bool,
+    pub friday: bool,
+    pub saturday: bool,
+    pub sunday: bool,
+}
+
+impl Default for LocalTimeCondition {
+    fn default() -> LocalTimeCondition {
+        Self {
+            from: 22 * 60,
+            to: 6 * 60,
+            weekdays: Weekdays::default(),
+        }
+    }
+}
+
+impl Default for Weekdays {
+    fn default() -> Weekdays {
+        Self {
+            monday: true,
+            tuesday: true,
+            wednesday: true,
+            thursday: true,
+            friday: true,
+            saturday: true,
+            sunday: true,
+        }
+    }
+}
+
+impl LocalTimeCondition {
+    pub fn is_fulfilled_at(&self, time: DateTime<Local>) -> bool {
+        self.weekdays.contains(time.weekday())
+            && is_minute_in_range(local_minute_of_day(time), self.from, self.to)
+    }
+}
+
+impl Weekdays {
+    pub fn contains(&self, weekday: Weekday) -> bool {
+        match weekday {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        }
+    }
+}
+
+pub fn local_minute_of_day(time: DateTime<Local>) -> u32 {
+    time.hour() * 60 + time.minute()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    // 2024-06-03 is a Monday, far from daylight saving time changes.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        let time = NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap();
+        Local.from_local_datetime(&time).single().unwrap()
+    }
+
+    fn weekends() -> Weekdays {
+        Weekdays {
+            monday: false,
+            tuesday: false,
+            wednesday: false,
+            thursday: false,
+            friday: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn range_boundaries() {
+        let condition = LocalTimeCondition {
+            from: 8 * 60,
+            to: 17 * 60 + 30,
+            weekdays: Weekdays::default(),
+        };
+        assert!(!condition.is_fulfilled_at(at(3, 7, 59)));
+        assert!(condition.is_fulfilled_at(at(3, 8, 0)));
+        assert!(condition.is_fulfilled_at(at(3, 17, 29)));
+        assert!(!condition.is_fulfilled_at(at(3, 17, 30)));
+    }
+
+    #[test]
+    fn wraps_around_midnight() {
+        let condition = LocalTimeCondition::default();
+        assert!(!condition.is_fulfilled_at(at(3, 21, 59)));
+        assert!(condition.is_fulfilled_at(at(3, 22, 0)));
+        assert!(condition.is_fulfilled_at(at(3, 23, 59)));
+        assert!(condition.is_fulfilled_at(at(4, 0, 0)));
+        assert!(condition.is_fulfilled_at(at(4, 5, 59)));
+        assert!(!condition.is_fulfilled_at(at(4, 6, 0)));
+    }
+
+    // Weekdays are checked against the current day, also after midnight.
+    #[test]
+    fn weekdays_after_midnight() {
+        let condition = LocalTimeCondition {
+            weekdays: weekends(),
+            ..Default::default()
+        };
+        assert!(!condition.is_fulfilled_at(at(7, 23, 0)));
+        assert!(condition.is_fulfilled_at(at(8, 0, 30)));
+        assert!(condition.is_fulfilled_at(at(8, 23, 0)));
+        assert!(condition.is_fulfilled_at(at(9, 23, 0)));
+        assert!(!condition.is_fulfilled_at(at(10, 0, 30)));
+    }
+
+    #[test]
+    fn weekdays_map_to_days() {
+        let weekdays = weekends();
+        assert!(!weekdays.contains(Weekday::Mon));
+        assert!(!weekdays.contains(Weekday::Fri));
+        assert!(weekdays.contains(Weekday::Sat));
+        assert!(weekdays.contains(Weekday::Sun));
+    }
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct MetaEventCondition {
+    pub event_name: String,
+    pub minutes_before: u32,
+}
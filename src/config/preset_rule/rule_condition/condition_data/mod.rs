@@ -1,6 +1,18 @@
+pub mod date_range;
+pub mod local_time_condition;
+pub mod meta_event_condition;
+pub mod speed_range;
 pub mod time_periods;
 
+use crate::config::preset_rule::rule_condition::condition_data::date_range::DateRange;
+use crate::config::preset_rule::rule_condition::condition_data::local_time_condition::LocalTimeCondition;
+use crate::config::preset_rule::rule_condition::condition_data::meta_event_condition::MetaEventCondition;
+use crate::config::preset_rule::rule_condition::condition_data::speed_range::SpeedRange;
 use crate::config::preset_rule::rule_condition::condition_data::time_periods::TimePeriods;
+use crate::context::time_period::{CurrentTimePeriod, EvaluationTime};
+use crate::context::Context;
+use chrono::Local;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,4 +21,146 @@ pub enum ConditionData {
     BlacklistedMaps(Vec<u32>),
     Time(TimePeriods),
     Chance(f32),
+    Speed(SpeedRange),
+    MetaEvent(MetaEventCondition),
+    GameClock { from: u32, to: u32 },
+    LocalTime(LocalTimeCondition),
+    Calendar(DateRange),
+}
+
+impl ConditionData {
+    // Initial data of every condition kind offered in the rule editor.
+    pub fn new_conditions() -> Vec<ConditionData> {
+        vec![
+            ConditionData::Maps(Vec::new()),
+            ConditionData::BlacklistedMaps(Vec::new()),
+            ConditionData::Time(TimePeriods::default()),
+            ConditionData::Chance(0.0),
+            ConditionData::Speed(SpeedRange::default()),
+            ConditionData::MetaEvent(MetaEventCondition::default()),
+            ConditionData::GameClock {
+                from: 18 * 60,
+                to: 20 * 60,
+            },
+            ConditionData::LocalTime(LocalTimeCondition::default()),
+            ConditionData::Calendar(DateRange::default()),
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConditionData::Maps(_) => "Map",
+            ConditionData::BlacklistedMaps(_) => "Blacklisted map",
+            ConditionData::Time(_) => "Time",
+            ConditionData::Chance(_) => "Chance",
+            ConditionData::Speed(_) => "Speed",
+            ConditionData::MetaEvent(_) => "Meta event",
+            ConditionData::GameClock { .. } => "Game clock",
+            ConditionData::LocalTime(_) => "Local time",
+            ConditionData::Calendar(_) => "Calendar",
+        }
+    }
+
+    pub fn is_fulfilled(
+        &self,
+        context: &Context,
+        current_map_id: &u32,
+        evaluation_time: &EvaluationTime,
+    ) -> bool {
+        match self {
+            ConditionData::Maps(maps) => maps.contains(current_map_id),
+            ConditionData::BlacklistedMaps(maps) => !maps.contains(current_map_id),
+            ConditionData::Time(time_periods) => match evaluation_time.time_period {
+                CurrentTimePeriod::Day => time_periods.day,
+                CurrentTimePeriod::Dusk => time_periods.dusk,
+                CurrentTimePeriod::Night => time_periods.night,
+                CurrentTimePeriod::Dawn => time_periods.dawn,
+            },
+            ConditionData::Chance(chance) => {
+                let mut gen = rand::thread_rng();
+                let roll = gen.gen_range(0.0..=1.0);
+                roll <= *chance
+            }
+            ConditionData::Speed(speed_range) => speed_range.contains(
+                context.movement.horizontal_speed,
+                context.movement.vertical_speed,
+            ),
+            ConditionData::MetaEvent(meta_event_condition) => context
+                .meta_events
+                .iter()
+                .find(|meta_event| meta_event.name == meta_event_condition.event_name)
+                .is_some_and(|meta_event| {
+                    meta_event.is_active_or_starting_within(
+                        evaluation_time.time,
+                        meta_event_condition.minutes_before,
+                    )
+                }),
+            ConditionData::GameClock { from, to } => {
+                is_minute_in_range(evaluation_time.game_clock_minute, *from, *to)
+            }
+            ConditionData::LocalTime(local_time_condition) => {
+                local_time_condition.is_fulfilled_at(evaluation_time.time.with_timezone(&Local))
+            }
+            ConditionData::Calendar(date_range) => {
+                date_range.contains(evaluation_time.time.with_timezone(&Local).date_naive())
+            }
+        }
+    }
+
+    // Polled conditions can change at any moment, so they are re-checked on every background tick.
+    pub fn is_polled(&self) -> bool {
+        matches!(
+            self,
+            ConditionData::Speed(_)
+                | ConditionData::MetaEvent(_)
+                | ConditionData::GameClock { .. }
+                | ConditionData::LocalTime(_)
+                | ConditionData::Calendar(_)
+        )
+    }
+}
+
+// Ranges where "from" is later than "to" wrap around midnight.
+pub fn is_minute_in_range(minute: u32, from: u32, to: u32) -> bool {
+    if from <= to {
+        from <= minute && minute < to
+    } else {
+        minute >= from || minute < to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u32 = 60;
+
+    #[test]
+    fn minute_range_includes_start_and_excludes_end() {
+        let (from, to) = (18 * HOUR, 20 * HOUR);
+        assert!(!is_minute_in_range(from - 1, from, to));
+        assert!(is_minute_in_range(from, from, to));
+        assert!(is_minute_in_range(to - 1, from, to));
+        assert!(!is_minute_in_range(to, from, to));
+    }
+
+    #[test]
+    fn minute_range_wraps_around_midnight() {
+        let (from, to) = (22 * HOUR, 6 * HOUR);
+        for minute in [22 * HOUR, 23 * HOUR + 59, 0, 6 * HOUR - 1] {
+            assert!(is_minute_in_range(minute, from, to), "{}", minute);
+        }
+        for minute in [22 * HOUR - 1, 6 * HOUR, 12 * HOUR] {
+            assert!(!is_minute_in_range(minute, from, to), "{}", minute);
+        }
+    }
+
+    #[test]
+    fn minute_range_edge_cases() {
+        assert!(!is_minute_in_range(0, 0, 0));
+        assert!(!is_minute_in_range(12 * HOUR, 12 * HOUR, 12 * HOUR));
+        assert!(is_minute_in_range(0, 0, 24 * HOUR));
+        assert!(is_minute_in_range(24 * HOUR - 1, 0, 24 * HOUR));
+        assert!(is_minute_in_range(0, 23 * HOUR + 59, 1));
+    }
 }
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_HORIZONTAL_SPEED: f32 = 50.0;
+pub const MAX_VERTICAL_SPEED: f32 = 30.0;
+const SPEED_HYSTERESIS: f32 = 2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpeedRange {
+    pub min_horizontal: f32,
+    pub max_horizontal: f32,
+    pub min_vertical: f32,
+    pub max_vertical: f32,
+}
+
+impl Default for SpeedRange {
+    fn default() -> SpeedRange {
+        Self {
+            min_horizontal: 10.0,
+            max_horizontal: MAX_HORIZONTAL_SPEED,
+            min_vertical: -MAX_VERTICAL_SPEED,
+            max_vertical: MAX_VERTICAL_SPEED,
+        }
+    }
+}
+
+impl SpeedRange {
+    pub fn contains(&self, horizontal_speed: f32, vertical_speed: f32) -> bool {
+        self.contains_with_margin(horizontal_speed, vertical_speed, 0.0)
+    }
+
+    // Once inside, the range is widened so speeds around a threshold don't toggle the condition.
+    pub fn contains_with_hysteresis(
+        &self,
+        horizontal_speed: f32,
+        vertical_speed: f32,
+        was_inside: bool,
+    ) -> bool {
+        let margin = if was_inside { SPEED_HYSTERESIS } else { 0.0 };
+        self.contains_with_margin(horizontal_speed, vertical_speed, margin)
+    }
+
+    //slider limits are treated as unbounded
+    fn contains_with_margin(
+        &self,
+        horizontal_speed: f32,
+        vertical_speed: f32,
+        margin: f32,
+    ) -> bool {
+        let horizontal = horizontal_speed >= self.min_horizontal - margin
+            && (self.max_horizontal >= MAX_HORIZONTAL_SPEED
+                || horizontal_speed <= self.max_horizontal + margin);
+        let vertical = (self.min_vertical <= -MAX_VERTICAL_SPEED
+            || vertical_speed >= self.min_vertical - margin)
+            && (self.max_vertical >= MAX_VERTICAL_SPEED
+                || vertical_speed <= self.max_vertical + margin);
+        horizontal && vertical
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walking() -> SpeedRange {
+        SpeedRange {
+            min_horizontal: 5.0,
+            max_horizontal: 10.0,
+            min_vertical: -MAX_VERTICAL_SPEED,
+            max_vertical: MAX_VERTICAL_SPEED,
+        }
+    }
+
+    #[test]
+    fn slider_limits_are_unbounded() {
+        let range = SpeedRange::default();
+        assert!(range.contains(MAX_HORIZONTAL_SPEED * 2.0, -MAX_VERTICAL_SPEED * 2.0));
+        assert!(!range.contains(9.9, 0.0));
+        assert!(walking().contains(10.0, 0.0));
+        assert!(!walking().contains(10.1, 0.0));
+    }
+
+    #[test]
+    fn hysteresis_keeps_range_until_clearly_outside() {
+        let range = walking();
+        assert!(!range.contains_with_hysteresis(4.0, 0.0, false));
+        assert!(range.contains_with_hysteresis(5.0, 0.0, false));
+        assert!(range.contains_with_hysteresis(4.0, 0.0, true));
+        assert!(range.contains_with_hysteresis(11.5, 0.0, true));
+        assert!(!range.contains_with_hysteresis(2.9, 0.0, true));
+        assert!(!range.contains_with_hysteresis(12.1, 0.0, true));
+    }
+}
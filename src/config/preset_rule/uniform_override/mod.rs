@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+// Replaces a uniform value of an effect, e.g. "Tonemap.fx: Exposure=-0.2".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UniformOverride {
+    pub effect_file: String,
+    pub key: String,
+    pub value: String,
+}
+
+impl fmt::Display for UniformOverride {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}={}", self.effect_file, self.key, self.value)
+    }
+}
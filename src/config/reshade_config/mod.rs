@@ -1,7 +1,13 @@
+use crate::util::reshade::switch_backend::SwitchBackendKind;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ReshadeConfig {
     pub ini_path: PathBuf,
+    #[serde(default)]
+    pub switch_backend: SwitchBackendKind,
+    // ReShade's own transition duration while a rule overrides it.
+    #[serde(default)]
+    pub original_transition_duration: Option<String>,
 }
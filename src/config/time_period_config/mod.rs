@@ -0,0 +1,42 @@
+use crate::context::time_period::CurrentTimePeriod;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_CLOCK_OFFSET_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimePeriodConfig {
+    #[serde(default = "default_true")]
+    pub use_real_time_api: bool,
+    #[serde(default = "default_true")]
+    pub use_clock: bool,
+    #[serde(default = "default_clock_offset_seconds")]
+    pub clock_offset_seconds: i64,
+    #[serde(default = "default_manual_time_period")]
+    pub manual_time_period: CurrentTimePeriod,
+    #[serde(default)]
+    pub lead_time_seconds: u32,
+}
+
+impl Default for TimePeriodConfig {
+    fn default() -> Self {
+        Self {
+            use_real_time_api: true,
+            use_clock: true,
+            clock_offset_seconds: DEFAULT_CLOCK_OFFSET_SECONDS,
+            manual_time_period: default_manual_time_period(),
+            lead_time_seconds: 0,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_clock_offset_seconds() -> i64 {
+    DEFAULT_CLOCK_OFFSET_SECONDS
+}
+
+fn default_manual_time_period() -> CurrentTimePeriod {
+    CurrentTimePeriod::Day
+}
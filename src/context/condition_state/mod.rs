@@ -0,0 +1,135 @@
+use crate::config::preset_rule::rule_condition::condition_data::ConditionData;
+use crate::config::preset_rule::PresetRule;
+use crate::context::movement::MovementContext;
+use rand::Rng;
+use std::collections::HashMap;
+
+// Rule id and index of the condition within the rule.
+type ConditionKey = (String, usize);
+
+// Results kept between evaluations, so polled conditions don't flip presets while moving.
+#[derive(Debug, Clone, Default)]
+pub struct ConditionState {
+    chance_rolls: HashMap<ConditionKey, f32>,
+    speed_matches: HashMap<ConditionKey, bool>,
+}
+
+impl ConditionState {
+    // Chance is only rolled again when the map, time period or game state changed.
+    pub fn roll_chances(&mut self, preset_rules: &[PresetRule], reroll: bool) {
+        if reroll {
+            self.chance_rolls.clear();
+        }
+        let mut rng = rand::thread_rng();
+        let mut chance_rolls = HashMap::new();
+        for rule in preset_rules {
+            for (condition_index, rule_condition) in rule.conditions.iter().enumerate() {
+                if let ConditionData::Chance(_) = rule_condition.data {
+                    let key = (rule.id.clone(), condition_index);
+                    let roll = self
+                        .chance_rolls
+                        .get(&key)
+                        .copied()
+                        .unwrap_or_else(|| rng.gen_range(0.0..=1.0));
+                    chance_rolls.insert(key, roll);
+                }
+            }
+        }
+        self.chance_rolls = chance_rolls;
+    }
+
+    pub fn chance_fulfilled(&self, rule_id: &str, condition_index: usize, chance: f32) -> bool {
+        self.chance_rolls
+            .get(&(rule_id.to_string(), condition_index))
+            .is_some_and(|roll| *roll <= chance)
+    }
+
+    // Speed ranges are left only once the speed is clearly outside, see SpeedRange::contains_with_hysteresis.
+    pub fn update_speed_matches(
+        &mut self,
+        preset_rules: &[PresetRule],
+        movement: &MovementContext,
+    ) {
+        let mut speed_matches = HashMap::new();
+        for rule in preset_rules {
+            for (condition_index, rule_condition) in rule.conditions.iter().enumerate() {
+                if let ConditionData::Speed(speed_range) = &rule_condition.data {
+                    let key = (rule.id.clone(), condition_index);
+                    let was_inside = self.speed_matches.get(&key).copied().unwrap_or(false);
+                    let inside = speed_range.contains_with_hysteresis(
+                        movement.horizontal_speed,
+                        movement.vertical_speed,
+                        was_inside,
+                    );
+                    speed_matches.insert(key, inside);
+                }
+            }
+        }
+        self.speed_matches = speed_matches;
+    }
+
+    pub fn speed_fulfilled(&self, rule_id: &str, condition_index: usize) -> bool {
+        self.speed_matches
+            .get(&(rule_id.to_string(), condition_index))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::preset_rule::rule_condition::condition_data::speed_range::SpeedRange;
+    use crate::config::preset_rule::rule_condition::conjunction_type::ConjunctionType;
+    use crate::config::preset_rule::rule_condition::RuleCondition;
+
+    fn rule(conditions: Vec<ConditionData>) -> PresetRule {
+        PresetRule {
+            conditions: conditions
+                .into_iter()
+                .map(|data| RuleCondition::new(data, ConjunctionType::And))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn chance_is_kept_until_rerolled() {
+        let rules = vec![rule(vec![ConditionData::Chance(0.5)])];
+        let mut condition_state = ConditionState::default();
+        condition_state.roll_chances(&rules, false);
+        let first_roll = condition_state.chance_rolls.clone();
+        for _ in 0..10 {
+            condition_state.roll_chances(&rules, false);
+            assert_eq!(condition_state.chance_rolls, first_roll);
+        }
+        assert!(condition_state.chance_fulfilled(&rules[0].id, 0, 1.0));
+        assert!(!condition_state.chance_fulfilled(&rules[0].id, 1, 1.0));
+
+        let rerolled = (0..20).any(|_| {
+            condition_state.roll_chances(&rules, true);
+            condition_state.chance_rolls != first_roll
+        });
+        assert!(rerolled);
+    }
+
+    #[test]
+    fn speed_matches_use_hysteresis() {
+        let rules = vec![rule(vec![
+            ConditionData::Chance(1.0),
+            ConditionData::Speed(SpeedRange::default()),
+        ])];
+        let mut condition_state = ConditionState::default();
+        let mut movement = MovementContext::default();
+        let mut speed_matches = |horizontal_speed: f32| {
+            movement.horizontal_speed = horizontal_speed;
+            condition_state.update_speed_matches(&rules, &movement);
+            condition_state.speed_fulfilled(&rules[0].id, 1)
+        };
+        assert!(!speed_matches(9.0));
+        assert!(speed_matches(10.0));
+        assert!(speed_matches(9.0));
+        assert!(!speed_matches(7.5));
+        assert!(!speed_matches(9.0));
+    }
+}
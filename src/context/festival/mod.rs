@@ -0,0 +1,17 @@
+use crate::config::load_data_file;
+use crate::config::preset_rule::rule_condition::condition_data::date_range::MonthDay;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_FESTIVALS: &str = include_str!("festivals.json");
+
+// Festival dates shift slightly every year, so they are kept in an editable file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Festival {
+    pub name: String,
+    pub from: MonthDay,
+    pub to: MonthDay,
+}
+
+pub fn load_festivals() -> Vec<Festival> {
+    load_data_file("festivals.json", DEFAULT_FESTIVALS)
+}
@@ -0,0 +1,109 @@
+use crate::config::preset_rule::PresetRule;
+use crate::config::time_period_config::TimePeriodConfig;
+use crate::context::time_period::day_night_cycle::DayNightCycle;
+use crate::context::time_period::{
+    clock_time, seconds_into_cycle, EvaluationTime, TimePeriodSource,
+};
+use crate::context::Context;
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+
+pub const FORECAST_HOURS: i64 = 4;
+
+#[derive(Debug, Clone)]
+pub struct Forecast {
+    pub map_id: u32,
+    pub segments: Vec<ForecastSegment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForecastSegment {
+    pub evaluation_time: EvaluationTime,
+    pub end: DateTime<Utc>,
+    pub rule_index: Option<usize>,
+    // Rules before the predicted one that depend on chance.
+    pub possible_rule_indices: Vec<usize>,
+}
+
+impl Forecast {
+    pub fn is_outdated(&self, current_map_id: &u32) -> bool {
+        self.map_id != *current_map_id
+            || self
+                .segments
+                .first()
+                .map(|segment| segment.end <= Utc::now())
+                .unwrap_or(true)
+    }
+}
+
+impl Context {
+    // Segments follow the clock thresholds, shifted by the lead time the same way live detection is.
+    // The game clock itself is not shifted.
+    pub fn forecast(
+        &self,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+        preset_rules: &[PresetRule],
+    ) -> Forecast {
+        let now = Utc::now().trunc_subsecs(0);
+        let forecast_end = now + Duration::hours(FORECAST_HOURS);
+        let lead_clock_offset_seconds =
+            time_period_config.clock_offset_seconds + time_period_config.lead_time_seconds as i64;
+        let manual_cycle = DayNightCycle::Fixed(time_period_config.manual_time_period.clone());
+        let cycle = match self.time_period_source {
+            TimePeriodSource::Manual => &manual_cycle,
+            _ => self.day_night_cycles.cycle_for_map(current_map_id),
+        };
+
+        let mut segments = Vec::new();
+        let mut start = now;
+        while start < forecast_end {
+            let lead_clock_time = clock_time(start, lead_clock_offset_seconds);
+            let end = match cycle {
+                DayNightCycle::Cycle(thresholds) => {
+                    let (seconds_until_next, _) =
+                        thresholds.next_boundary(seconds_into_cycle(lead_clock_time));
+                    (start + Duration::seconds(seconds_until_next)).min(forecast_end)
+                }
+                DayNightCycle::Fixed(_) => forecast_end,
+            };
+            let evaluation_time = EvaluationTime {
+                time: start,
+                time_period: cycle.time_period_at(lead_clock_time),
+                game_clock_minute: cycle
+                    .game_minute_at(clock_time(start, time_period_config.clock_offset_seconds)),
+            };
+            let (rule_index, possible_rule_indices) =
+                rule_index_to_activate(preset_rules, self, current_map_id, &evaluation_time);
+            segments.push(ForecastSegment {
+                evaluation_time,
+                end,
+                rule_index,
+                possible_rule_indices,
+            });
+            start = end;
+        }
+        Forecast {
+            map_id: *current_map_id,
+            segments,
+        }
+    }
+}
+
+// Same outcome as rule processing: first fulfilled rule wins, the last rule is the default.
+// Rules depending on chance are collected on the way, as they may win instead.
+fn rule_index_to_activate(
+    preset_rules: &[PresetRule],
+    context: &Context,
+    current_map_id: &u32,
+    evaluation_time: &EvaluationTime,
+) -> (Option<usize>, Vec<usize>) {
+    let mut possible_rule_indices = Vec::new();
+    for (rule_index, preset_rule) in preset_rules.iter().enumerate() {
+        match preset_rule.predict(context, current_map_id, evaluation_time) {
+            Some(true) => return (Some(rule_index), possible_rule_indices),
+            None => possible_rule_indices.push(rule_index),
+            Some(false) => {}
+        }
+    }
+    (preset_rules.len().checked_sub(1), possible_rule_indices)
+}
@@ -0,0 +1,106 @@
+use crate::context::Context;
+use function_name::named;
+use log::info;
+use std::fmt;
+use std::fmt::Formatter;
+use std::time::{Duration, Instant};
+
+const UI_TICK_STALL_TIMEOUT: Duration = Duration::from_secs(2);
+const LOADING_SCREEN_TIMEOUT: Duration = Duration::from_secs(90);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameState {
+    InGame,
+    LoadingScreen,
+    CharacterSelect,
+}
+
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            GameState::InGame => "In game",
+            GameState::LoadingScreen => "Loading screen",
+            GameState::CharacterSelect => "Character select",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameStateContext {
+    pub current: GameState,
+    last_ui_tick: u32,
+    last_ui_tick_change: Instant,
+    loading_screen_start: Option<Instant>,
+}
+
+impl Default for GameStateContext {
+    fn default() -> Self {
+        Self {
+            current: GameState::CharacterSelect,
+            last_ui_tick: 0,
+            last_ui_tick_change: Instant::now(),
+            loading_screen_start: None,
+        }
+    }
+}
+
+impl Context {
+    pub fn is_in_game(&self) -> bool {
+        self.game_state.current == GameState::InGame
+    }
+
+    // Neither MumbleLink nor Nexus tell loading screens and character select apart,
+    // so a gameplay interruption counts as a loading screen until it lasts suspiciously long.
+    #[named]
+    pub fn game_state_changed(&mut self) -> bool {
+        let is_gameplay = unsafe { self.links.nexus() }
+            .map(|nexus| nexus.is_gameplay)
+            .unwrap_or(false);
+        let ui_tick_stalled = self.ui_tick_stalled();
+        let game_state = &mut self.game_state;
+
+        let new_state = if is_gameplay && !ui_tick_stalled {
+            GameState::InGame
+        } else {
+            match game_state.current {
+                GameState::InGame => GameState::LoadingScreen,
+                GameState::LoadingScreen => match game_state.loading_screen_start {
+                    Some(start) if start.elapsed() > LOADING_SCREEN_TIMEOUT => {
+                        GameState::CharacterSelect
+                    }
+                    _ => GameState::LoadingScreen,
+                },
+                GameState::CharacterSelect => GameState::CharacterSelect,
+            }
+        };
+
+        if new_state == game_state.current {
+            return false;
+        }
+        game_state.loading_screen_start = match new_state {
+            GameState::LoadingScreen => Some(Instant::now()),
+            _ => None,
+        };
+        game_state.current = new_state;
+        info!(
+            "[{}] Game state changed to {:?}",
+            function_name!(),
+            new_state
+        );
+        true
+    }
+
+    // Without MumbleLink the Nexus gameplay flag is used alone.
+    fn ui_tick_stalled(&mut self) -> bool {
+        if let Some(m) = self.links.mumble {
+            let ui_tick = m.read_ui_tick();
+            if ui_tick != self.game_state.last_ui_tick {
+                self.game_state.last_ui_tick = ui_tick;
+                self.game_state.last_ui_tick_change = Instant::now();
+            }
+            return self.game_state.last_ui_tick_change.elapsed() > UI_TICK_STALL_TIMEOUT;
+        }
+        false
+    }
+}
@@ -0,0 +1,133 @@
+use crate::config::load_data_file;
+use chrono::{DateTime, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_META_EVENTS: &str = include_str!("meta_events.json");
+const SECONDS_IN_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetaEvent {
+    pub name: String,
+    // UTC, HH:MM
+    pub start_times: Vec<String>,
+    pub duration_minutes: u32,
+}
+
+impl MetaEvent {
+    pub fn seconds_until_active(&self, time: DateTime<Utc>) -> Option<i64> {
+        let now = time.num_seconds_from_midnight() as i64;
+        let duration = self.duration_minutes as i64 * 60;
+        self.start_times
+            .iter()
+            .filter_map(|start_time| NaiveTime::parse_from_str(start_time, "%H:%M").ok())
+            .map(|start_time| {
+                let start = start_time.num_seconds_from_midnight() as i64;
+                if (now - start).rem_euclid(SECONDS_IN_DAY) < duration {
+                    0
+                } else {
+                    (start - now).rem_euclid(SECONDS_IN_DAY)
+                }
+            })
+            .min()
+    }
+
+    pub fn is_active_or_starting_within(&self, time: DateTime<Utc>, minutes: u32) -> bool {
+        self.seconds_until_active(time)
+            .is_some_and(|seconds| seconds <= minutes as i64 * 60)
+    }
+}
+
+pub fn load_meta_events() -> Vec<MetaEvent> {
+    load_data_file("meta_events.json", DEFAULT_META_EVENTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const MINUTE: i64 = 60;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, hour, minute, 0).unwrap()
+    }
+
+    fn meta_event(start_times: &[&str], duration_minutes: u32) -> MetaEvent {
+        MetaEvent {
+            name: "Meta".to_string(),
+            start_times: start_times.iter().map(|time| time.to_string()).collect(),
+            duration_minutes,
+        }
+    }
+
+    #[test]
+    fn active_from_start_until_end() {
+        let event = meta_event(&["12:00"], 15);
+        assert_eq!(event.seconds_until_active(at(11, 59)), Some(MINUTE));
+        assert_eq!(event.seconds_until_active(at(12, 0)), Some(0));
+        assert_eq!(event.seconds_until_active(at(12, 14)), Some(0));
+        assert_eq!(
+            event.seconds_until_active(at(12, 15)),
+            Some(24 * 60 * MINUTE - 15 * MINUTE)
+        );
+    }
+
+    #[test]
+    fn nearest_start_time_is_used() {
+        let event = meta_event(&["01:00", "09:00", "17:00"], 20);
+        assert_eq!(event.seconds_until_active(at(8, 30)), Some(30 * MINUTE));
+        assert_eq!(
+            event.seconds_until_active(at(17, 30)),
+            Some(7 * 60 * MINUTE + 30 * MINUTE)
+        );
+    }
+
+    #[test]
+    fn wraps_around_midnight() {
+        let event = meta_event(&["23:45"], 30);
+        assert_eq!(event.seconds_until_active(at(23, 30)), Some(15 * MINUTE));
+        assert_eq!(event.seconds_until_active(at(23, 50)), Some(0));
+        assert_eq!(event.seconds_until_active(at(0, 14)), Some(0));
+        assert_eq!(
+            event.seconds_until_active(at(0, 15)),
+            Some(23 * 60 * MINUTE + 30 * MINUTE)
+        );
+        let event = meta_event(&["00:10"], 15);
+        assert_eq!(event.seconds_until_active(at(23, 55)), Some(15 * MINUTE));
+    }
+
+    #[test]
+    fn starting_within() {
+        let event = meta_event(&["00:05"], 15);
+        assert!(event.is_active_or_starting_within(at(23, 55), 10));
+        assert!(!event.is_active_or_starting_within(at(23, 54), 10));
+        assert!(event.is_active_or_starting_within(at(0, 10), 0));
+        assert!(!event.is_active_or_starting_within(at(0, 20), 0));
+    }
+
+    #[test]
+    fn invalid_start_times_are_skipped() {
+        assert_eq!(meta_event(&[], 15).seconds_until_active(at(12, 0)), None);
+        assert_eq!(
+            meta_event(&["25:00", "noon"], 15).seconds_until_active(at(12, 0)),
+            None
+        );
+        assert_eq!(
+            meta_event(&["noon", "12:30"], 15).seconds_until_active(at(12, 0)),
+            Some(30 * MINUTE)
+        );
+    }
+
+    #[test]
+    fn default_schedule_parses() {
+        let meta_events: Vec<MetaEvent> = serde_json::from_str(DEFAULT_META_EVENTS).unwrap();
+        assert!(!meta_events.is_empty());
+        for meta_event in &meta_events {
+            assert!(
+                meta_event.seconds_until_active(at(0, 0)).is_some(),
+                "{}",
+                meta_event.name
+            );
+        }
+    }
+}
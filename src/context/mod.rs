@@ -1,21 +1,36 @@
+pub mod condition_state;
+pub mod festival;
+pub mod forecast;
+pub mod game_state;
 mod links;
+pub mod meta_event;
+pub mod movement;
 pub mod reshade_context;
 pub mod time_period;
 pub mod ui;
 
 use crate::addon::Addon;
+use crate::config::preset_rule::PresetRule;
+use crate::config::time_period_config::{TimePeriodConfig, DEFAULT_CLOCK_OFFSET_SECONDS};
+use crate::context::condition_state::ConditionState;
+use crate::context::festival::{load_festivals, Festival};
+use crate::context::game_state::GameStateContext;
 use crate::context::links::Links;
+use crate::context::meta_event::{load_meta_events, MetaEvent};
+use crate::context::movement::MovementContext;
 use crate::context::reshade_context::ReshadeContext;
-use crate::context::time_period::{
-    canthan_time_thresholds, current_time_period_with_default_detection, tyrian_time_thresholds,
-    CurrentTimePeriod,
-};
+use crate::context::time_period::day_night_cycle::{load_day_night_cycles, DayNightCycles};
+use crate::context::time_period::{clock_time, CurrentTimePeriod, TimePeriodSource};
 use crate::context::ui::UiContext;
+use crate::thread::detect_reshade_ini_files_thread;
 use crate::util::reshade::load_reshade_context;
+use crate::util::reshade::transition::{
+    record_original_transition_duration, restore_transition_duration,
+};
+use chrono::Utc;
 use function_name::named;
-use log::error;
+use log::debug;
 use log::info;
-use nexus::rtapi::WorldData;
 
 #[derive(Debug, Clone)]
 pub struct Context {
@@ -25,7 +40,16 @@ pub struct Context {
     pub ui: UiContext,
     pub reshade: ReshadeContext,
     pub current_time_period: CurrentTimePeriod,
+    pub time_period_source: TimePeriodSource,
+    pub game_clock_minute: u32,
     pub process_manually: bool,
+    pub game_state: GameStateContext,
+    pub movement: MovementContext,
+    pub polled_condition_results: Vec<bool>,
+    pub condition_state: ConditionState,
+    pub meta_events: Vec<MetaEvent>,
+    pub day_night_cycles: DayNightCycles,
+    pub festivals: Vec<Festival>,
 }
 
 impl Default for Context {
@@ -36,16 +60,29 @@ impl Default for Context {
             previous_map_id: None,
             ui: UiContext::default(),
             reshade: ReshadeContext::default(),
-            current_time_period: current_time_period_with_default_detection(
-                tyrian_time_thresholds(),
-            ),
+            current_time_period: DayNightCycles::default()
+                .default_cycle()
+                .time_period_at(clock_time(Utc::now(), DEFAULT_CLOCK_OFFSET_SECONDS)),
+            time_period_source: TimePeriodSource::Clock,
+            game_clock_minute: 0,
             process_manually: false,
+            game_state: GameStateContext::default(),
+            movement: MovementContext::default(),
+            polled_condition_results: Vec::new(),
+            condition_state: ConditionState::default(),
+            meta_events: Vec::new(),
+            day_night_cycles: DayNightCycles::default(),
+            festivals: Vec::new(),
         }
     }
 }
 impl Context {
     #[named]
-    pub fn map_changed(&mut self, new_map_id: &mut u32) -> bool {
+    pub fn map_changed(
+        &mut self,
+        new_map_id: &mut u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> bool {
         if let Some(m) = self.links.mumble {
             let current_map = m.read_map_id();
             let changed;
@@ -59,19 +96,11 @@ impl Context {
 
             if changed {
                 info!("[{}] Map changed to {}", function_name!(), new_map_id);
-                if let Some(rtapi) = &self.links.rtapi {
-                    let world_data = unsafe { WorldData::read(rtapi) };
-                    self.current_time_period = match world_data.time_of_day {
-                        Ok(time_of_day) => CurrentTimePeriod::from(time_of_day),
-                        Err(e) => {
-                            error!("Error reading rtapi time of day: {}", e);
-                            self.detect_time_period_with_default_detection(new_map_id)
-                        }
-                    };
-                } else {
-                    self.current_time_period =
-                        self.detect_time_period_with_default_detection(new_map_id);
-                };
+                self.movement.reset();
+                (self.current_time_period, self.time_period_source) =
+                    self.detect_time_period(new_map_id, time_period_config);
+                self.game_clock_minute =
+                    self.detect_game_clock_minute(new_map_id, time_period_config);
 
                 info!(
                     "[{}] Current time period updated on map change: {:?}",
@@ -84,15 +113,24 @@ impl Context {
         false
     }
 
-    fn detect_time_period_with_default_detection(
+    #[named]
+    pub fn polled_conditions_changed(
         &mut self,
-        new_map_id: &mut u32,
-    ) -> CurrentTimePeriod {
-        if canthan_time_maps().contains(new_map_id) {
-            current_time_period_with_default_detection(canthan_time_thresholds())
-        } else {
-            current_time_period_with_default_detection(tyrian_time_thresholds())
+        preset_rules: &[PresetRule],
+        current_map_id: &u32,
+    ) -> bool {
+        self.condition_state
+            .update_speed_matches(preset_rules, &self.movement);
+        let results: Vec<bool> = preset_rules
+            .iter()
+            .flat_map(|preset_rule| preset_rule.polled_condition_results(self, current_map_id))
+            .collect();
+        if results != self.polled_condition_results {
+            self.polled_condition_results = results;
+            debug!("[{}] Polled condition results changed", function_name!());
+            return true;
         }
+        false
     }
 
     pub fn valid(&self) -> bool {
@@ -100,13 +138,30 @@ impl Context {
     }
 }
 
+fn record_transition_duration_if_overridden() {
+    let mut addon = Addon::lock();
+    if addon
+        .config
+        .preset_rules
+        .iter()
+        .any(|rule| rule.transition_duration_ms.is_some())
+        && record_original_transition_duration(&mut addon.config.reshade)
+    {
+        addon.config.save();
+    }
+}
+
 pub fn init_context() {
+    Addon::lock().context.meta_events = load_meta_events();
+    Addon::lock().context.day_night_cycles = load_day_night_cycles();
+    Addon::lock().context.festivals = load_festivals();
     if Addon::lock().config.valid() {
         let reshade_ini_path = Addon::lock().config.reshade.ini_path.clone();
         load_reshade_context(&reshade_ini_path);
+        // Left over when the game was closed without unloading the addon.
+        restore_transition_duration();
+        record_transition_duration_if_overridden();
+    } else {
+        detect_reshade_ini_files_thread();
     }
 }
-
-fn canthan_time_maps() -> Vec<u32> {
-    vec![1442, 1438, 1452, 1422, 1490, 1428, 1465, 1593, 1595]
-}
@@ -0,0 +1,166 @@
+use crate::context::Context;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+const SAMPLE_WINDOW: usize = 4;
+const SMOOTHING_FACTOR: f32 = 0.5;
+const MIN_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 100.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct MovementContext {
+    samples: VecDeque<PositionSample>,
+    pub horizontal_speed: f32,
+    pub vertical_speed: f32,
+}
+
+#[derive(Debug, Clone)]
+struct PositionSample {
+    position: [f32; 3],
+    time: Instant,
+}
+
+impl MovementContext {
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.horizontal_speed = 0.0;
+        self.vertical_speed = 0.0;
+    }
+
+    pub fn sample(&mut self, position: [f32; 3]) {
+        self.sample_at(position, Instant::now());
+    }
+
+    fn sample_at(&mut self, position: [f32; 3], time: Instant) {
+        let sample = PositionSample { position, time };
+        if let Some(previous) = self.samples.back() {
+            let (horizontal, vertical) = speed_between(previous, &sample);
+            //teleports, waypoints and map loads are not movement
+            if horizontal.hypot(vertical) > MAX_SPEED {
+                self.reset();
+                self.samples.push_back(sample);
+                return;
+            }
+        }
+        self.samples.push_back(sample);
+        if self.samples.len() > SAMPLE_WINDOW {
+            self.samples.pop_front();
+        }
+        if let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) {
+            let (horizontal, vertical) = speed_between(first, last);
+            self.horizontal_speed = smooth(self.horizontal_speed, horizontal);
+            self.vertical_speed = smooth(self.vertical_speed, vertical);
+        }
+    }
+}
+
+impl Context {
+    pub fn update_movement(&mut self) {
+        if !self.is_in_game() {
+            self.movement.reset();
+            return;
+        }
+        if let Some(m) = self.links.mumble {
+            let position = m.read_avatar().position;
+            self.movement.sample([position.x, position.y, position.z]);
+        }
+    }
+}
+
+fn speed_between(from: &PositionSample, to: &PositionSample) -> (f32, f32) {
+    let seconds = to.time.duration_since(from.time).as_secs_f32();
+    if seconds <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let dx = to.position[0] - from.position[0];
+    let dy = to.position[1] - from.position[1];
+    let dz = to.position[2] - from.position[2];
+    (dx.hypot(dz) / seconds, dy / seconds)
+}
+
+fn smooth(previous: f32, current: f32) -> f32 {
+    let smoothed = previous + SMOOTHING_FACTOR * (current - previous);
+    if smoothed.abs() < MIN_SPEED {
+        0.0
+    } else {
+        smoothed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Samples one second apart.
+    fn sample_path(movement: &mut MovementContext, start: Instant, positions: &[[f32; 3]]) {
+        for (second, position) in positions.iter().enumerate() {
+            movement.sample_at(*position, start + Duration::from_secs(second as u64));
+        }
+    }
+
+    #[test]
+    fn speed_is_smoothed() {
+        let mut movement = MovementContext::default();
+        let start = Instant::now();
+        sample_path(&mut movement, start, &[[0.0, 0.0, 0.0], [8.0, 0.0, 6.0]]);
+        assert_eq!(movement.horizontal_speed, 5.0);
+        movement.sample_at([16.0, -2.0, 12.0], start + Duration::from_secs(2));
+        assert_eq!(movement.horizontal_speed, 7.5);
+        assert_eq!(movement.vertical_speed, -0.5);
+    }
+
+    #[test]
+    fn slow_drift_counts_as_standing() {
+        let mut movement = MovementContext::default();
+        sample_path(
+            &mut movement,
+            Instant::now(),
+            &[[0.0, 0.0, 0.0], [0.4, 0.2, 0.3], [0.8, 0.4, 0.6]],
+        );
+        assert_eq!(movement.horizontal_speed, 0.0);
+        assert_eq!(movement.vertical_speed, 0.0);
+    }
+
+    #[test]
+    fn teleports_do_not_cause_speed_spikes() {
+        let mut movement = MovementContext::default();
+        let start = Instant::now();
+        sample_path(
+            &mut movement,
+            start,
+            &[[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [20.0, 0.0, 0.0]],
+        );
+        assert_eq!(movement.horizontal_speed, 7.5);
+
+        movement.sample_at([5000.0, 0.0, 0.0], start + Duration::from_secs(3));
+        assert_eq!(movement.horizontal_speed, 0.0);
+        assert_eq!(movement.vertical_speed, 0.0);
+        movement.sample_at([5010.0, 0.0, 0.0], start + Duration::from_secs(4));
+        assert_eq!(movement.horizontal_speed, 5.0);
+    }
+
+    #[test]
+    fn speed_just_below_limit_is_kept() {
+        let mut movement = MovementContext::default();
+        sample_path(
+            &mut movement,
+            Instant::now(),
+            &[[0.0, 0.0, 0.0], [0.0, -MAX_SPEED, 0.0]],
+        );
+        assert_eq!(movement.vertical_speed, -MAX_SPEED / 2.0);
+    }
+
+    #[test]
+    fn reset_on_map_change_starts_over() {
+        let mut movement = MovementContext::default();
+        let start = Instant::now();
+        sample_path(&mut movement, start, &[[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]]);
+        movement.reset();
+        assert_eq!(movement.horizontal_speed, 0.0);
+        movement.sample_at([0.0, 0.0, 0.0], start + Duration::from_secs(2));
+        assert_eq!(movement.horizontal_speed, 0.0);
+        movement.sample_at([4.0, 0.0, 0.0], start + Duration::from_secs(3));
+        assert_eq!(movement.horizontal_speed, 2.0);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod virtual_key;
+
+use crate::context::reshade_context::key_combination::virtual_key::{
+    is_valid_virtual_key, virtual_key_name,
+};
+use crate::util::true_if_1;
 use function_name::named;
 use log::error;
 use rdev::{EventType, Key};
@@ -25,6 +31,46 @@ impl Default for KeyCombination {
     }
 }
 
+impl KeyCombination {
+    // ReShade stores keybinds as "key code,ctrl,shift,alt".
+    pub fn from_reshade_values(values: &[String]) -> Option<KeyCombination> {
+        Some(KeyCombination {
+            key_code: values.first()?.clone(),
+            ctrl: values.get(1).map(true_if_1()).unwrap_or(false),
+            shift: values.get(2).map(true_if_1()).unwrap_or(false),
+            alt: values.get(3).map(true_if_1()).unwrap_or(false),
+        })
+    }
+
+    pub fn to_reshade_values(&self) -> [String; 4] {
+        [
+            self.key_code.clone(),
+            bool_to_string(self.ctrl),
+            bool_to_string(self.shift),
+            bool_to_string(self.alt),
+        ]
+    }
+
+    pub fn virtual_key(&self) -> Option<u32> {
+        self.key_code
+            .parse::<u32>()
+            .ok()
+            .filter(|code| is_valid_virtual_key(*code))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.virtual_key().is_some()
+    }
+}
+
+fn bool_to_string(b: bool) -> String {
+    if b {
+        "1".to_string()
+    } else {
+        "0".to_string()
+    }
+}
+
 impl fmt::Display for KeyCombination {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut result = "".to_string();
@@ -37,10 +83,9 @@ impl fmt::Display for KeyCombination {
         if self.alt {
             result.push_str("Alt+");
         }
-        if let Ok(code) = self.key_code.parse::<u32>() {
-            if let Some(ch) = char::from_u32(code) {
-                result.push(ch);
-            }
+        match self.key_code.parse::<u32>().ok().and_then(virtual_key_name) {
+            Some(name) => result.push_str(&name),
+            None => result.push_str(&format!("Unknown key ({})", self.key_code)),
         }
         write!(f, "{}", result)
     }
@@ -48,6 +93,31 @@ impl fmt::Display for KeyCombination {
 
 #[named]
 pub fn trigger_key_combination(key_combination: &KeyCombination) {
+    let Some(keys) = rdev_keys(key_combination) else {
+        error!(
+            "[{}] Invalid virtual-key code in reshade keybind: {}",
+            function_name!(),
+            key_combination.key_code
+        );
+        return;
+    };
+
+    for key in &keys {
+        crate::util::send(&EventType::KeyPress(*key));
+    }
+
+    thread::sleep(Duration::from_millis(30));
+
+    for key in &keys {
+        crate::util::send(&EventType::KeyRelease(*key));
+    }
+
+    thread::sleep(Duration::from_millis(1500));
+}
+
+// Modifiers are pressed before the main key, which rdev sends by its virtual-key code.
+fn rdev_keys(key_combination: &KeyCombination) -> Option<Vec<Key>> {
+    let virtual_key = key_combination.virtual_key()?;
     let mut keys = vec![];
     if key_combination.ctrl {
         keys.push(Key::ControlLeft);
@@ -58,27 +128,107 @@ pub fn trigger_key_combination(key_combination: &KeyCombination) {
     if key_combination.alt {
         keys.push(Key::Alt);
     }
+    keys.push(Key::Unknown(virtual_key));
+    Some(keys)
+}
 
-    if let Ok(key) = key_combination.key_code.parse() {
-        keys.push(Key::Unknown(key));
-    } else {
-        error!(
-            "[{}] Could not parse reshade keybind: {}",
-            function_name!(),
-            key_combination.key_code
-        );
-        return;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::reshade_context::key_combination::virtual_key::virtual_key_code;
+
+    const MODIFIERS: [(bool, bool, bool); 8] = [
+        (false, false, false),
+        (true, false, false),
+        (false, true, false),
+        (false, false, true),
+        (true, true, false),
+        (true, false, true),
+        (false, true, true),
+        (true, true, true),
+    ];
+
+    fn key_combinations() -> impl Iterator<Item = KeyCombination> {
+        (0x00..=0xFF).flat_map(|code| {
+            MODIFIERS
+                .iter()
+                .map(move |&(ctrl, shift, alt)| KeyCombination {
+                    key_code: code.to_string(),
+                    ctrl,
+                    shift,
+                    alt,
+                })
+        })
     }
 
-    for key in &keys {
-        crate::util::send(&EventType::KeyPress(*key));
+    #[test]
+    fn names_and_codes_round_trip() {
+        let mut count = 0;
+        for code in (0x00..=0xFF).filter(|code| is_valid_virtual_key(*code)) {
+            let name = virtual_key_name(code).unwrap();
+            assert!(!name.is_empty(), "{:#04X}", code);
+            assert_eq!(virtual_key_code(&name), Some(code), "{}", name);
+            count += 1;
+        }
+        // 0-9, A-Z, numpad digits, F1-F24 and named keys.
+        assert_eq!(count, 10 + 26 + 10 + 24 + 86);
+        assert_eq!(virtual_key_name(0x7B).as_deref(), Some("F12"));
+        assert_eq!(virtual_key_name(0x87).as_deref(), Some("F24"));
+        assert_eq!(virtual_key_name(0x65).as_deref(), Some("Numpad 5"));
+        assert_eq!(virtual_key_name(0x41).as_deref(), Some("A"));
+        assert_eq!(virtual_key_name(0xBB).as_deref(), Some("="));
+        assert_eq!(virtual_key_code("Unknown"), None);
     }
 
-    thread::sleep(Duration::from_millis(30));
+    #[test]
+    fn mouse_buttons_and_modifiers_are_invalid() {
+        for code in [
+            0x00, 0x01, 0x02, 0x04, 0x05, 0x06, 0x10, 0x11, 0x12, 0xA0, 0xA5, 0xFF, 0x100,
+        ] {
+            assert!(!is_valid_virtual_key(code), "{:#04X}", code);
+        }
+    }
 
-    for key in &keys {
-        crate::util::send(&EventType::KeyRelease(*key));
+    #[test]
+    fn reshade_values_round_trip() {
+        for key_combination in key_combinations() {
+            let values = key_combination.to_reshade_values();
+            assert_eq!(
+                KeyCombination::from_reshade_values(&values),
+                Some(key_combination.clone())
+            );
+        }
+        let values: Vec<String> = "124,1,0,1".split(',').map(String::from).collect();
+        let key_combination = KeyCombination::from_reshade_values(&values).unwrap();
+        assert_eq!(key_combination.to_string(), "Ctrl+Alt+F13");
+        assert_eq!(key_combination.to_reshade_values().join(","), "124,1,0,1");
     }
 
-    thread::sleep(Duration::from_millis(1500));
+    #[test]
+    fn rdev_keys_for_every_key() {
+        for key_combination in key_combinations() {
+            let code: u32 = key_combination.key_code.parse().unwrap();
+            let keys = rdev_keys(&key_combination);
+            if !is_valid_virtual_key(code) {
+                assert_eq!(keys, None, "{}", code);
+                continue;
+            }
+            let keys = keys.unwrap();
+            assert_eq!(keys.last(), Some(&Key::Unknown(code)));
+            assert_eq!(keys.contains(&Key::ControlLeft), key_combination.ctrl);
+            assert_eq!(keys.contains(&Key::ShiftLeft), key_combination.shift);
+            assert_eq!(keys.contains(&Key::Alt), key_combination.alt);
+            assert_eq!(
+                keys.len(),
+                1 + [
+                    key_combination.ctrl,
+                    key_combination.shift,
+                    key_combination.alt
+                ]
+                .iter()
+                .filter(|modifier| **modifier)
+                .count()
+            );
+        }
+    }
 }
@@ -0,0 +1,110 @@
+// Windows virtual-key codes, as stored by ReShade in keybind settings.
+const NAMED_KEYS: [(u32, &str); 86] = [
+    (0x03, "Cancel"),
+    (0x08, "Backspace"),
+    (0x09, "Tab"),
+    (0x0C, "Clear"),
+    (0x0D, "Enter"),
+    (0x13, "Pause"),
+    (0x14, "Caps Lock"),
+    (0x15, "IME Kana"),
+    (0x17, "IME Junja"),
+    (0x18, "IME Final"),
+    (0x19, "IME Kanji"),
+    (0x1B, "Escape"),
+    (0x1C, "IME Convert"),
+    (0x1D, "IME Nonconvert"),
+    (0x1E, "IME Accept"),
+    (0x1F, "IME Mode Change"),
+    (0x20, "Space"),
+    (0x21, "Page Up"),
+    (0x22, "Page Down"),
+    (0x23, "End"),
+    (0x24, "Home"),
+    (0x25, "Left Arrow"),
+    (0x26, "Up Arrow"),
+    (0x27, "Right Arrow"),
+    (0x28, "Down Arrow"),
+    (0x29, "Select"),
+    (0x2A, "Print"),
+    (0x2B, "Execute"),
+    (0x2C, "Print Screen"),
+    (0x2D, "Insert"),
+    (0x2E, "Delete"),
+    (0x2F, "Help"),
+    (0x5B, "Left Windows"),
+    (0x5C, "Right Windows"),
+    (0x5D, "Apps"),
+    (0x5F, "Sleep"),
+    (0x6A, "Numpad *"),
+    (0x6B, "Numpad +"),
+    (0x6C, "Numpad Separator"),
+    (0x6D, "Numpad -"),
+    (0x6E, "Numpad ."),
+    (0x6F, "Numpad /"),
+    (0x90, "Num Lock"),
+    (0x91, "Scroll Lock"),
+    (0xA6, "Browser Back"),
+    (0xA7, "Browser Forward"),
+    (0xA8, "Browser Refresh"),
+    (0xA9, "Browser Stop"),
+    (0xAA, "Browser Search"),
+    (0xAB, "Browser Favorites"),
+    (0xAC, "Browser Home"),
+    (0xAD, "Volume Mute"),
+    (0xAE, "Volume Down"),
+    (0xAF, "Volume Up"),
+    (0xB0, "Next Track"),
+    (0xB1, "Previous Track"),
+    (0xB2, "Stop Media"),
+    (0xB3, "Play/Pause Media"),
+    (0xB4, "Launch Mail"),
+    (0xB5, "Select Media"),
+    (0xB6, "Launch App 1"),
+    (0xB7, "Launch App 2"),
+    (0xBA, ";"),
+    (0xBB, "="),
+    (0xBC, ","),
+    (0xBD, "-"),
+    (0xBE, "."),
+    (0xBF, "/"),
+    (0xC0, "`"),
+    (0xDB, "["),
+    (0xDC, "\\"),
+    (0xDD, "]"),
+    (0xDE, "'"),
+    (0xDF, "OEM 8"),
+    (0xE1, "OEM AX"),
+    (0xE2, "<"),
+    (0xE5, "IME Process"),
+    (0xE9, "OEM Reset"),
+    (0xF6, "Attn"),
+    (0xF7, "CrSel"),
+    (0xF8, "ExSel"),
+    (0xF9, "Erase EOF"),
+    (0xFA, "Play"),
+    (0xFB, "Zoom"),
+    (0xFD, "PA1"),
+    (0xFE, "OEM Clear"),
+];
+
+// Mouse buttons and modifiers have codes too, but cannot be used as the main key of a keybind.
+pub fn virtual_key_name(code: u32) -> Option<String> {
+    match code {
+        0x30..=0x39 | 0x41..=0x5A => char::from_u32(code).map(String::from),
+        0x60..=0x69 => Some(format!("Numpad {}", code - 0x60)),
+        0x70..=0x87 => Some(format!("F{}", code - 0x6F)),
+        _ => NAMED_KEYS
+            .iter()
+            .find(|(key_code, _)| *key_code == code)
+            .map(|(_, name)| name.to_string()),
+    }
+}
+
+pub fn is_valid_virtual_key(code: u32) -> bool {
+    virtual_key_name(code).is_some()
+}
+
+pub fn virtual_key_code(name: &str) -> Option<u32> {
+    (0x00..=0xFF).find(|code| virtual_key_name(*code).as_deref() == Some(name))
+}
@@ -1,16 +1,34 @@
 pub mod key_combination;
+pub mod preset;
 
 use crate::context::reshade_context::key_combination::KeyCombination;
+use crate::context::reshade_context::preset::ReshadePreset;
+use crate::util::reshade::ini_watch::ReshadeIniWatch;
+use crate::util::reshade::ipc::IpcConnection;
+use crate::util::reshade::preset_path::PresetPaths;
+use crate::util::reshade::shortcut_repair::ShortcutRepair;
+use crate::util::reshade::{PRESET_SHORTCUT_KEYS, PRESET_SHORTCUT_PATHS};
 use bimap::BiMap;
 use function_name::named;
 use log::info;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Default)]
 pub struct ReshadeContext {
     pub active_preset_path: PathBuf,
     pub preset_shortcuts: BiMap<KeyCombination, PathBuf>,
     pub verify_activation: Option<(PathBuf, usize)>,
+    // Transition duration of the rule being activated, applied by switching through ReShade.ini.
+    pub transition_duration_ms: Option<u32>,
+    pub applied_shortcut_repair: Option<ShortcutRepair>,
+    pub shortcut_repair_overwritten: bool,
+    pub shortcut_repair_reapplied: u32,
+    pub presets: HashMap<PathBuf, ReshadePreset>,
+    pub preset_paths: PresetPaths,
+    pub ini_watch: ReshadeIniWatch,
+    pub reload_key: Option<KeyCombination>,
+    pub ipc: IpcConnection,
 }
 
 impl ReshadeContext {
@@ -18,8 +36,12 @@ impl ReshadeContext {
         !self.preset_shortcuts.is_empty()
     }
 
+    // Waits for pending ReShade.ini changes, ReShade saves the new preset path with a delay.
     #[named]
     pub fn should_retry_activation(&mut self) -> bool {
+        if self.ini_watch.change_pending() {
+            return false;
+        }
         if let Some((preset_path, mut retries)) = self.verify_activation.as_ref() {
             if self.active_preset_path == *preset_path {
                 self.verify_activation = None;
@@ -40,40 +62,48 @@ impl ReshadeContext {
         false
     }
 
-    pub fn as_reshade_shortcut_configuration(&self) -> String {
-        let preset_shortcut_paths = format!(
-            "PresetShortcutPaths={}",
-            self.preset_shortcuts
-                .iter()
-                .map(|(_, path)| path.to_string_lossy().to_string())
-                .collect::<Vec<String>>()
-                .join(",")
-        );
-        let mut preset_shortcut_keys = String::new();
-        preset_shortcut_keys.push_str("PresetShortcutKeys=");
+    pub fn resolve(&self, preset_path: &Path) -> PathBuf {
+        self.preset_paths.resolve(preset_path)
+    }
 
-        let iter = &mut self.preset_shortcuts.iter().peekable();
-        while let Some((key_combination, _)) = iter.next() {
-            preset_shortcut_keys.push_str(key_combination.key_code.as_str());
-            preset_shortcut_keys.push(',');
-            preset_shortcut_keys.push_str(bool_to_string(key_combination.ctrl).as_str());
-            preset_shortcut_keys.push(',');
-            preset_shortcut_keys.push_str(bool_to_string(key_combination.shift).as_str());
-            preset_shortcut_keys.push(',');
-            preset_shortcut_keys.push_str(bool_to_string(key_combination.alt).as_str());
+    pub fn preset_exists(&self, preset_path: &Path) -> bool {
+        self.preset_paths.exists(preset_path)
+    }
 
-            if iter.peek().is_some() {
-                preset_shortcut_keys.push(',');
-            }
-        }
-        format!("{}\n{}", preset_shortcut_keys, preset_shortcut_paths)
+    // Paths are written back the way ReShade.ini spells them.
+    pub fn ini_spelling(&self, preset_path: &Path) -> String {
+        self.preset_paths.spelling(preset_path)
+    }
+
+    pub fn technique_summary(&self, preset_path: &Path) -> Option<String> {
+        self.presets
+            .get(preset_path)
+            .map(|preset| preset.technique_summary())
+    }
+
+    pub fn as_reshade_shortcut_configuration(&self) -> String {
+        format!(
+            "{}={}\n{}={}",
+            PRESET_SHORTCUT_KEYS,
+            self.shortcut_keys_value(),
+            PRESET_SHORTCUT_PATHS,
+            self.shortcut_paths_value()
+        )
+    }
+
+    pub fn shortcut_paths_value(&self) -> String {
+        self.preset_shortcuts
+            .iter()
+            .map(|(_, path)| self.ini_spelling(path))
+            .collect::<Vec<String>>()
+            .join(",")
     }
-}
 
-fn bool_to_string(b: bool) -> String {
-    if b {
-        "1".to_string()
-    } else {
-        "0".to_string()
+    pub fn shortcut_keys_value(&self) -> String {
+        self.preset_shortcuts
+            .iter()
+            .flat_map(|(key_combination, _)| key_combination.to_reshade_values())
+            .collect::<Vec<String>>()
+            .join(",")
     }
 }
@@ -0,0 +1,175 @@
+use crate::context::reshade_context::preset::{ReshadePreset, Technique};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresetDiff {
+    pub added_techniques: Vec<Technique>,
+    pub removed_techniques: Vec<Technique>,
+    pub changed_values: Vec<ValueChange>,
+}
+
+// Missing values fall back to the shader default in ReShade, so they are shown as such.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub effect_file: String,
+    pub key: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl PresetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_techniques.is_empty()
+            && self.removed_techniques.is_empty()
+            && self.changed_values.is_empty()
+    }
+}
+
+impl ReshadePreset {
+    pub fn diff(&self, other: &ReshadePreset) -> PresetDiff {
+        let added_techniques = other
+            .techniques
+            .iter()
+            .filter(|technique| !self.techniques.contains(technique))
+            .cloned()
+            .collect();
+        let removed_techniques = self
+            .techniques
+            .iter()
+            .filter(|technique| !other.techniques.contains(technique))
+            .cloned()
+            .collect();
+
+        let mut changed_values = vec![];
+        let mut effect_files: Vec<&String> = self
+            .effects
+            .iter()
+            .chain(other.effects.iter())
+            .map(|effect| &effect.effect_file)
+            .collect();
+        effect_files.sort();
+        effect_files.dedup();
+        for effect_file in effect_files {
+            let mut keys: Vec<&String> = self
+                .effect(effect_file)
+                .into_iter()
+                .chain(other.effect(effect_file))
+                .flat_map(|effect| effect.values.iter().map(|(key, _)| key))
+                .collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let from = self.effect(effect_file).and_then(|effect| effect.get(key));
+                let to = other.effect(effect_file).and_then(|effect| effect.get(key));
+                if from != to {
+                    changed_values.push(ValueChange {
+                        effect_file: effect_file.clone(),
+                        key: key.clone(),
+                        from: from.cloned(),
+                        to: to.cloned(),
+                    });
+                }
+            }
+        }
+
+        PresetDiff {
+            added_techniques,
+            removed_techniques,
+            changed_values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::ini::Ini;
+
+    const DAY: &str = "Techniques=Tonemap@Tonemap.fx,Vibrance@Vibrance.fx,LumaSharpen\n\
+        [Tonemap.fx]\n\
+        Exposure=0.000000\n\
+        Gamma=1.000000\n\
+        [Vibrance.fx]\n\
+        Vibrance=0.150000\n";
+
+    fn preset(content: &str) -> ReshadePreset {
+        ReshadePreset::from_ini(&Ini::parse(content))
+    }
+
+    fn technique(name: &str, effect_file: Option<&str>) -> Technique {
+        Technique {
+            name: name.to_string(),
+            effect_file: effect_file.map(String::from),
+        }
+    }
+
+    fn change(effect_file: &str, key: &str, from: Option<&str>, to: Option<&str>) -> ValueChange {
+        ValueChange {
+            effect_file: effect_file.to_string(),
+            key: key.to_string(),
+            from: from.map(String::from),
+            to: to.map(String::from),
+        }
+    }
+
+    #[test]
+    fn same_preset_has_no_changes() {
+        assert!(preset(DAY).diff(&preset(DAY)).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_techniques() {
+        let night = preset(
+            "Techniques=Tonemap@Tonemap.fx,LumaSharpen@LumaSharpen.fx,DOF@DOF.fx\n\
+            [Tonemap.fx]\n\
+            Exposure=0.000000\n\
+            Gamma=1.000000\n\
+            [Vibrance.fx]\n\
+            Vibrance=0.150000\n",
+        );
+        let diff = preset(DAY).diff(&night);
+        assert_eq!(
+            diff.added_techniques,
+            [
+                technique("LumaSharpen", Some("LumaSharpen.fx")),
+                technique("DOF", Some("DOF.fx"))
+            ]
+        );
+        assert_eq!(
+            diff.removed_techniques,
+            [
+                technique("Vibrance", Some("Vibrance.fx")),
+                technique("LumaSharpen", None)
+            ]
+        );
+        assert!(diff.changed_values.is_empty());
+    }
+
+    #[test]
+    fn changed_added_and_removed_uniforms() {
+        let night = preset(
+            "Techniques=Tonemap@Tonemap.fx,Vibrance@Vibrance.fx,LumaSharpen\n\
+            [Tonemap.fx]\n\
+            Exposure=-0.300000\n\
+            Saturation=0.100000\n\
+            [Curves.fx]\n\
+            Contrast=0.150000\n",
+        );
+        let diff = preset(DAY).diff(&night);
+        assert!(diff.added_techniques.is_empty() && diff.removed_techniques.is_empty());
+        assert_eq!(
+            diff.changed_values,
+            [
+                change("Curves.fx", "Contrast", None, Some("0.150000")),
+                change(
+                    "Tonemap.fx",
+                    "Exposure",
+                    Some("0.000000"),
+                    Some("-0.300000")
+                ),
+                change("Tonemap.fx", "Gamma", Some("1.000000"), None),
+                change("Tonemap.fx", "Saturation", None, Some("0.100000")),
+                change("Vibrance.fx", "Vibrance", Some("0.150000"), None),
+            ]
+        );
+    }
+}
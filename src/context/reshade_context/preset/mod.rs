@@ -0,0 +1,123 @@
+pub mod diff;
+
+use crate::util::ini::Ini;
+use std::fmt::Formatter;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+use std::{fmt, fs};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReshadePreset {
+    pub techniques: Vec<Technique>,
+    pub technique_sorting: Vec<Technique>,
+    pub effects: Vec<EffectSettings>,
+    pub modified: Option<SystemTime>,
+}
+
+// Stored as "Name@Effect.fx", older presets omit the effect file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Technique {
+    pub name: String,
+    pub effect_file: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectSettings {
+    pub effect_file: String,
+    pub values: Vec<(String, String)>,
+}
+
+impl ReshadePreset {
+    pub fn read(path: &Path) -> io::Result<ReshadePreset> {
+        let mut preset = ReshadePreset::from_ini(&Ini::read(path)?);
+        preset.modified = fs::metadata(path)?.modified().ok();
+        Ok(preset)
+    }
+
+    pub fn from_ini(ini: &Ini) -> ReshadePreset {
+        let techniques = |key| {
+            ini.get_list("", key)
+                .iter()
+                .map(|technique| Technique::parse(technique))
+                .collect()
+        };
+        ReshadePreset {
+            techniques: techniques("Techniques"),
+            technique_sorting: techniques("TechniqueSorting"),
+            effects: ini
+                .sections()
+                .filter(|section| !section.name.is_empty())
+                .map(|section| EffectSettings {
+                    effect_file: section.name.clone(),
+                    values: section
+                        .entries()
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                })
+                .collect(),
+            modified: None,
+        }
+    }
+
+    pub fn effect(&self, effect_file: &str) -> Option<&EffectSettings> {
+        self.effects
+            .iter()
+            .find(|effect| effect.effect_file == effect_file)
+    }
+
+    pub fn technique_names(&self) -> Vec<&str> {
+        self.techniques
+            .iter()
+            .map(|technique| technique.name.as_str())
+            .collect()
+    }
+
+    pub fn technique_summary(&self) -> String {
+        if self.techniques.is_empty() {
+            "No techniques enabled".to_string()
+        } else {
+            self.technique_names().join(", ")
+        }
+    }
+}
+
+impl EffectSettings {
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.values
+            .iter()
+            .find(|(value_key, _)| value_key == key)
+            .map(|(_, value)| value)
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.effect_file {
+            Some(effect_file) => write!(f, "{} ({})", self.name, effect_file),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl Technique {
+    pub fn to_reshade_value(&self) -> String {
+        match &self.effect_file {
+            Some(effect_file) => format!("{}@{}", self.name, effect_file),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn parse(technique: &str) -> Technique {
+        match technique.split_once('@') {
+            Some((name, effect_file)) => Technique {
+                name: name.trim().to_string(),
+                effect_file: Some(effect_file.trim().to_string()),
+            },
+            None => Technique {
+                name: technique.trim().to_string(),
+                effect_file: None,
+            },
+        }
+    }
+}
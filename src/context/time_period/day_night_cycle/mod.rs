@@ -0,0 +1,298 @@
+use crate::config::load_data_file;
+use crate::context::time_period::{
+    seconds_into_cycle, time_period_at, CurrentTimePeriod, CYCLE_SECONDS,
+};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DEFAULT_DAY_NIGHT_CYCLES: &str = include_str!("day_night_cycles.json");
+pub const MINUTES_IN_DAY: u32 = 24 * 60;
+const DAWN_START_MINUTE: u32 = 5 * 60;
+const DAY_START_MINUTE: u32 = 6 * 60;
+const DUSK_START_MINUTE: u32 = 20 * 60;
+const NIGHT_START_MINUTE: u32 = 21 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayNightCycles {
+    pub default_cycle: String,
+    pub cycles: HashMap<String, DayNightCycle>,
+    #[serde(default)]
+    pub maps: HashMap<u32, String>,
+}
+
+// Thresholds are seconds into the two hour cycle at which each period ends.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimeThresholds {
+    pub day_end: i64,
+    pub dusk_end: i64,
+    pub night_end: i64,
+    pub dawn_end: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DayNightCycle {
+    Cycle(TimeThresholds),
+    Fixed(CurrentTimePeriod),
+}
+
+impl Default for DayNightCycles {
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_DAY_NIGHT_CYCLES).expect("invalid default day/night cycles")
+    }
+}
+
+impl DayNightCycles {
+    pub fn cycle_for_map(&self, map_id: &u32) -> &DayNightCycle {
+        self.maps
+            .get(map_id)
+            .and_then(|cycle_name| self.cycles.get(cycle_name))
+            .unwrap_or_else(|| self.default_cycle())
+    }
+
+    pub fn default_cycle(&self) -> &DayNightCycle {
+        self.cycles
+            .get(&self.default_cycle)
+            .expect("default day/night cycle is validated on load")
+    }
+
+    fn validate(&self) -> bool {
+        let mut valid = true;
+        if !self.cycles.contains_key(&self.default_cycle) {
+            warn!("Unknown default day/night cycle: {}", self.default_cycle);
+            valid = false;
+        }
+        for (map_id, cycle_name) in &self.maps {
+            if !self.cycles.contains_key(cycle_name) {
+                warn!("Unknown day/night cycle {cycle_name} for map {map_id}");
+            }
+        }
+        for (cycle_name, cycle) in &self.cycles {
+            if let DayNightCycle::Cycle(thresholds) = cycle {
+                if !thresholds.is_ordered() {
+                    warn!("Day/night cycle {cycle_name} thresholds are not in ascending order");
+                    valid = false;
+                }
+            }
+        }
+        valid
+    }
+}
+
+impl DayNightCycle {
+    pub fn time_period_at(&self, clock_time: DateTime<Utc>) -> CurrentTimePeriod {
+        match self {
+            DayNightCycle::Cycle(thresholds) => time_period_at(clock_time, thresholds),
+            DayNightCycle::Fixed(time_period) => time_period.clone(),
+        }
+    }
+
+    pub fn game_minute_at(&self, clock_time: DateTime<Utc>) -> u32 {
+        match self {
+            DayNightCycle::Cycle(thresholds) => {
+                thresholds.game_minute(seconds_into_cycle(clock_time))
+            }
+            DayNightCycle::Fixed(time_period) => match time_period {
+                CurrentTimePeriod::Day => 13 * 60,
+                CurrentTimePeriod::Dusk => DUSK_START_MINUTE + 30,
+                CurrentTimePeriod::Night => 60,
+                CurrentTimePeriod::Dawn => DAWN_START_MINUTE + 30,
+            },
+        }
+    }
+}
+
+impl TimeThresholds {
+    // Each period is stretched over its usual in-game hours: dawn 5-6, day 6-20, dusk 20-21, night 21-5.
+    pub fn game_minute(&self, seconds_into_cycle: i64) -> u32 {
+        let seconds = if seconds_into_cycle <= self.day_end {
+            seconds_into_cycle + CYCLE_SECONDS
+        } else {
+            seconds_into_cycle
+        };
+        let segments = [
+            (
+                self.dawn_end,
+                self.day_end + CYCLE_SECONDS,
+                DAY_START_MINUTE,
+                DUSK_START_MINUTE,
+            ),
+            (
+                self.day_end,
+                self.dusk_end,
+                DUSK_START_MINUTE,
+                NIGHT_START_MINUTE,
+            ),
+            (
+                self.dusk_end,
+                self.night_end,
+                NIGHT_START_MINUTE,
+                DAWN_START_MINUTE + MINUTES_IN_DAY,
+            ),
+            (
+                self.night_end,
+                self.dawn_end,
+                DAWN_START_MINUTE,
+                DAY_START_MINUTE,
+            ),
+        ];
+        segments
+            .into_iter()
+            .find(|(start, end, _, _)| *start < seconds && seconds <= *end)
+            .map(|(start, end, start_minute, end_minute)| {
+                let progress = (seconds - start) as f64 / (end - start) as f64;
+                let minute = start_minute as f64 + progress * (end_minute - start_minute) as f64;
+                minute as u32 % MINUTES_IN_DAY
+            })
+            .unwrap_or(DAY_START_MINUTE)
+    }
+
+    // Seconds until the next period starts and the period that starts then.
+    pub fn next_boundary(&self, seconds_into_cycle: i64) -> (i64, CurrentTimePeriod) {
+        [
+            (self.day_end, CurrentTimePeriod::Dusk),
+            (self.dusk_end, CurrentTimePeriod::Night),
+            (self.night_end, CurrentTimePeriod::Dawn),
+            (self.dawn_end, CurrentTimePeriod::Day),
+        ]
+        .into_iter()
+        .map(|(period_end, next_period)| {
+            let seconds_until = (period_end + 1 - seconds_into_cycle).rem_euclid(CYCLE_SECONDS);
+            if seconds_until == 0 {
+                (CYCLE_SECONDS, next_period)
+            } else {
+                (seconds_until, next_period)
+            }
+        })
+        .min_by_key(|(seconds_until, _)| *seconds_until)
+        .unwrap()
+    }
+
+    fn is_ordered(&self) -> bool {
+        0 <= self.day_end
+            && self.day_end <= self.dusk_end
+            && self.dusk_end <= self.night_end
+            && self.night_end <= self.dawn_end
+    }
+}
+
+pub fn load_day_night_cycles() -> DayNightCycles {
+    let day_night_cycles: DayNightCycles =
+        load_data_file("day_night_cycles.json", DEFAULT_DAY_NIGHT_CYCLES);
+    if day_night_cycles.validate() {
+        day_night_cycles
+    } else {
+        warn!("Invalid day/night cycles, using defaults");
+        DayNightCycles::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    const MINUTE: i64 = 60;
+
+    fn at(seconds_into_cycle: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(seconds_into_cycle)
+    }
+
+    fn thresholds(cycle: &DayNightCycle) -> &TimeThresholds {
+        match cycle {
+            DayNightCycle::Cycle(thresholds) => thresholds,
+            DayNightCycle::Fixed(_) => panic!("expected a cycle"),
+        }
+    }
+
+    #[test]
+    fn default_cycles_are_valid() {
+        assert!(DayNightCycles::default().validate());
+    }
+
+    #[test]
+    fn periods_at_thresholds() {
+        let cycles = DayNightCycles::default();
+        for cycle_name in ["tyrian", "canthan"] {
+            let cycle = &cycles.cycles[cycle_name];
+            let t = thresholds(cycle);
+            let boundaries = [
+                (t.day_end, CurrentTimePeriod::Day, CurrentTimePeriod::Dusk),
+                (
+                    t.dusk_end,
+                    CurrentTimePeriod::Dusk,
+                    CurrentTimePeriod::Night,
+                ),
+                (
+                    t.night_end,
+                    CurrentTimePeriod::Night,
+                    CurrentTimePeriod::Dawn,
+                ),
+                (t.dawn_end, CurrentTimePeriod::Dawn, CurrentTimePeriod::Day),
+            ];
+            for (threshold, ending, starting) in boundaries {
+                assert_eq!(
+                    cycle.time_period_at(at(threshold - MINUTE)),
+                    ending,
+                    "{cycle_name} {threshold} - 1 min"
+                );
+                assert_eq!(
+                    cycle.time_period_at(at(threshold)),
+                    ending,
+                    "{cycle_name} {threshold}"
+                );
+                assert_eq!(
+                    cycle.time_period_at(at(threshold + MINUTE)),
+                    starting,
+                    "{cycle_name} {threshold} + 1 min"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let cycles = DayNightCycles::default();
+        let cycle = cycles.default_cycle();
+        assert_eq!(
+            cycle.time_period_at(at(CYCLE_SECONDS - MINUTE)),
+            CurrentTimePeriod::Day
+        );
+        assert_eq!(
+            cycle.time_period_at(at(CYCLE_SECONDS)),
+            CurrentTimePeriod::Day
+        );
+        assert_eq!(
+            cycle.time_period_at(at(CYCLE_SECONDS + MINUTE)),
+            CurrentTimePeriod::Day
+        );
+        assert_eq!(
+            cycle.time_period_at(at(12 * CYCLE_SECONDS - MINUTE)),
+            CurrentTimePeriod::Day
+        );
+        let t = thresholds(cycle);
+        assert_eq!(
+            t.next_boundary(CYCLE_SECONDS - MINUTE),
+            (t.day_end + 1 + MINUTE, CurrentTimePeriod::Dusk)
+        );
+    }
+
+    #[test]
+    fn game_minutes_at_thresholds() {
+        let cycles = DayNightCycles::default();
+        let t = thresholds(cycles.default_cycle());
+        assert_eq!(t.game_minute(t.dawn_end + 1), DAY_START_MINUTE);
+        assert_eq!(t.game_minute(t.day_end), DUSK_START_MINUTE);
+        assert_eq!(t.game_minute(t.dusk_end), NIGHT_START_MINUTE);
+        assert_eq!(t.game_minute(t.night_end), DAWN_START_MINUTE);
+        assert_eq!(t.game_minute(t.dawn_end), DAY_START_MINUTE);
+    }
+
+    #[test]
+    fn unknown_map_uses_default_cycle() {
+        let cycles = DayNightCycles::default();
+        assert_eq!(cycles.cycle_for_map(&u32::MAX), cycles.default_cycle());
+        assert_eq!(cycles.cycle_for_map(&1422), &cycles.cycles["canthan"]);
+    }
+}
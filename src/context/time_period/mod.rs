@@ -1,10 +1,19 @@
-use crate::context::{canthan_time_maps, Context};
+pub mod day_night_cycle;
+
+use crate::config::time_period_config::TimePeriodConfig;
+use crate::context::time_period::day_night_cycle::{DayNightCycle, TimeThresholds};
+use crate::context::Context;
 use chrono::{DateTime, Duration, Timelike, Utc};
 use function_name::named;
-use log::{error, info};
+use log::{debug, error, info};
 use nexus::rtapi::{TimeOfDay, WorldData};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+pub const CYCLE_SECONDS: i64 = 2 * 60 * 60;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum CurrentTimePeriod {
     Day,
     Dusk,
@@ -12,6 +21,24 @@ pub enum CurrentTimePeriod {
     Dawn,
 }
 
+impl CurrentTimePeriod {
+    pub const ALL: [CurrentTimePeriod; 4] = [
+        CurrentTimePeriod::Day,
+        CurrentTimePeriod::Dusk,
+        CurrentTimePeriod::Night,
+        CurrentTimePeriod::Dawn,
+    ];
+
+    pub fn next(&self) -> CurrentTimePeriod {
+        match self {
+            CurrentTimePeriod::Day => CurrentTimePeriod::Dusk,
+            CurrentTimePeriod::Dusk => CurrentTimePeriod::Night,
+            CurrentTimePeriod::Night => CurrentTimePeriod::Dawn,
+            CurrentTimePeriod::Dawn => CurrentTimePeriod::Day,
+        }
+    }
+}
+
 impl From<TimeOfDay> for CurrentTimePeriod {
     fn from(time_of_day: TimeOfDay) -> Self {
         match time_of_day {
@@ -23,85 +50,224 @@ impl From<TimeOfDay> for CurrentTimePeriod {
     }
 }
 
-impl Context {
-    #[named]
-    pub fn time_period_changed(&mut self, current_map_id: &mut u32) -> bool {
-        let mut time_thresholds = tyrian_time_thresholds();
-        if canthan_time_maps().contains(current_map_id) {
-            time_thresholds = canthan_time_thresholds();
+// Point in time rules are evaluated at. Rule processing uses the present, forecasts use future segments.
+#[derive(Debug, Clone)]
+pub struct EvaluationTime {
+    pub time: DateTime<Utc>,
+    pub time_period: CurrentTimePeriod,
+    pub game_clock_minute: u32,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TimePeriodSource {
+    RealTimeApi,
+    Clock,
+    Manual,
+}
+
+impl TimePeriodSource {
+    pub const CHAIN: [TimePeriodSource; 3] = [
+        TimePeriodSource::RealTimeApi,
+        TimePeriodSource::Clock,
+        TimePeriodSource::Manual,
+    ];
+
+    pub fn confidence(&self) -> &'static str {
+        match self {
+            TimePeriodSource::RealTimeApi => "high",
+            TimePeriodSource::Clock => "medium",
+            TimePeriodSource::Manual => "low",
         }
-        let new_period = if let Some(rtapi) = &self.links.rtapi {
-            let world_data = unsafe { WorldData::read(rtapi) };
-            match world_data.time_of_day {
-                Ok(time_of_day) => CurrentTimePeriod::from(time_of_day),
-                Err(e) => {
-                    error!("Error reading rtapi time of day: {}", e);
-                    current_time_period_with_default_detection(time_thresholds)
-                }
-            }
-        } else {
-            current_time_period_with_default_detection(time_thresholds)
+    }
+}
+
+impl fmt::Display for TimePeriodSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            TimePeriodSource::RealTimeApi => "RealTime API",
+            TimePeriodSource::Clock => "Clock",
+            TimePeriodSource::Manual => "Manual",
         };
+        write!(f, "{}", str)
+    }
+}
+
+impl Context {
+    pub fn evaluation_time_now(&self) -> EvaluationTime {
+        EvaluationTime {
+            time: Utc::now(),
+            time_period: self.current_time_period.clone(),
+            game_clock_minute: self.game_clock_minute,
+        }
+    }
+
+    #[named]
+    pub fn time_period_changed(
+        &mut self,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> bool {
+        let (new_period, source) = self.detect_time_period(current_map_id, time_period_config);
+        self.time_period_source = source;
+        self.game_clock_minute = self.detect_game_clock_minute(current_map_id, time_period_config);
 
         if new_period != self.current_time_period {
             self.current_time_period = new_period;
             info!(
-                "[{}] Time period changed to {:?}",
+                "[{}] Time period changed to {:?} (source: {})",
                 function_name!(),
-                self.current_time_period
+                self.current_time_period,
+                self.time_period_source
             );
             return true;
         }
         false
     }
-}
 
-fn last_even_hour(now: DateTime<Utc>) -> u32 {
-    if now.hour() % 2 == 1 {
-        now.hour() - 1
-    } else {
-        now.hour()
+    pub fn detect_time_period(
+        &self,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> (CurrentTimePeriod, TimePeriodSource) {
+        TimePeriodSource::CHAIN
+            .iter()
+            .find_map(|source| {
+                self.time_period_from(source, current_map_id, time_period_config)
+                    .map(|time_period| (time_period, *source))
+            })
+            .map(|(time_period, source)| match source {
+                TimePeriodSource::Manual => (time_period, source),
+                _ => (
+                    self.with_lead_time(time_period, current_map_id, time_period_config),
+                    source,
+                ),
+            })
+            .unwrap_or((
+                time_period_config.manual_time_period.clone(),
+                TimePeriodSource::Manual,
+            ))
     }
+
+    // Boundaries are predicted by the clock, so the next period is only accepted if it
+    // follows the detected one. This keeps RealTime API results intact when the clock drifts.
+    #[named]
+    fn with_lead_time(
+        &self,
+        time_period: CurrentTimePeriod,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> CurrentTimePeriod {
+        if time_period_config.lead_time_seconds == 0 {
+            return time_period;
+        }
+        if let DayNightCycle::Cycle(thresholds) =
+            self.day_night_cycles.cycle_for_map(current_map_id)
+        {
+            let clock_time = clock_time(Utc::now(), time_period_config.clock_offset_seconds);
+            let (seconds_until_next, next_period) =
+                thresholds.next_boundary(seconds_into_cycle(clock_time));
+            if seconds_until_next <= time_period_config.lead_time_seconds as i64
+                && next_period == time_period.next()
+            {
+                debug!(
+                    "[{}] Switching to {:?} {}s early",
+                    function_name!(),
+                    next_period,
+                    seconds_until_next
+                );
+                return next_period;
+            }
+        }
+        time_period
+    }
+
+    fn time_period_from(
+        &self,
+        source: &TimePeriodSource,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> Option<CurrentTimePeriod> {
+        match source {
+            TimePeriodSource::RealTimeApi => {
+                if !time_period_config.use_real_time_api {
+                    return None;
+                }
+                let rtapi = self.links.rtapi.as_ref()?;
+                let world_data = unsafe { WorldData::read(rtapi) };
+                match world_data.time_of_day {
+                    Ok(time_of_day) => Some(CurrentTimePeriod::from(time_of_day)),
+                    Err(e) => {
+                        error!("Error reading rtapi time of day: {}", e);
+                        None
+                    }
+                }
+            }
+            TimePeriodSource::Clock => time_period_config.use_clock.then(|| {
+                self.detect_time_period_with_default_detection(
+                    current_map_id,
+                    time_period_config.clock_offset_seconds,
+                )
+            }),
+            TimePeriodSource::Manual => Some(time_period_config.manual_time_period.clone()),
+        }
+    }
+
+    pub fn detect_time_period_with_default_detection(
+        &self,
+        current_map_id: &u32,
+        clock_offset_seconds: i64,
+    ) -> CurrentTimePeriod {
+        self.day_night_cycles
+            .cycle_for_map(current_map_id)
+            .time_period_at(clock_time(Utc::now(), clock_offset_seconds))
+    }
+
+    pub fn detect_game_clock_minute(
+        &self,
+        current_map_id: &u32,
+        time_period_config: &TimePeriodConfig,
+    ) -> u32 {
+        let clock_time = clock_time(Utc::now(), time_period_config.clock_offset_seconds);
+        self.day_night_cycles
+            .cycle_for_map(current_map_id)
+            .game_minute_at(clock_time)
+    }
+
+    // Offset that makes the clock report the start of dusk on the current map right now.
+    pub fn dusk_calibration_offset(&self, current_map_id: &u32) -> Option<i64> {
+        match self.day_night_cycles.cycle_for_map(current_map_id) {
+            DayNightCycle::Cycle(thresholds) => Some(
+                (thresholds.day_end + 1 - seconds_into_cycle(Utc::now())).rem_euclid(CYCLE_SECONDS),
+            ),
+            DayNightCycle::Fixed(_) => None,
+        }
+    }
+}
+
+pub fn clock_time(time: DateTime<Utc>, clock_offset_seconds: i64) -> DateTime<Utc> {
+    time + Duration::seconds(clock_offset_seconds)
 }
 
-pub fn current_time_period_with_default_detection(
-    thresholds: (i64, i64, i64, i64),
-) -> CurrentTimePeriod {
-    let current_time = Utc::now() + Duration::hours(1);
-    let hour = last_even_hour(current_time);
-    let day_start_time = current_time
-        .with_hour(hour)
-        .unwrap()
-        .with_minute(0)
-        .unwrap()
-        .with_second(0)
-        .unwrap();
-    let duration = current_time.signed_duration_since(day_start_time);
-    let seconds_between = duration.num_seconds();
-
-    time_period(&seconds_between, thresholds)
+pub fn seconds_into_cycle(clock_time: DateTime<Utc>) -> i64 {
+    (clock_time.num_seconds_from_midnight() as i64).rem_euclid(CYCLE_SECONDS)
 }
 
-fn time_period(seconds_between: &i64, time_thresholds: (i64, i64, i64, i64)) -> CurrentTimePeriod {
-    if seconds_between <= &time_thresholds.0 {
+pub fn time_period_at(clock_time: DateTime<Utc>, thresholds: &TimeThresholds) -> CurrentTimePeriod {
+    time_period(&seconds_into_cycle(clock_time), thresholds)
+}
+
+fn time_period(seconds_between: &i64, time_thresholds: &TimeThresholds) -> CurrentTimePeriod {
+    if seconds_between <= &time_thresholds.day_end {
         return CurrentTimePeriod::Day;
     }
-    if seconds_between <= &time_thresholds.1 {
+    if seconds_between <= &time_thresholds.dusk_end {
         return CurrentTimePeriod::Dusk;
     }
-    if seconds_between <= &time_thresholds.2 {
+    if seconds_between <= &time_thresholds.night_end {
         return CurrentTimePeriod::Night;
     }
-    if seconds_between <= &time_thresholds.3 {
+    if seconds_between <= &time_thresholds.dawn_end {
         return CurrentTimePeriod::Dawn;
     }
     CurrentTimePeriod::Day
 }
-
-pub fn tyrian_time_thresholds() -> (i64, i64, i64, i64) {
-    (2400, 2700, 5100, 5400)
-}
-
-pub fn canthan_time_thresholds() -> (i64, i64, i64, i64) {
-    (2100, 2400, 5700, 6000)
-}
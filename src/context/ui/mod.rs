@@ -1,4 +1,10 @@
+use crate::config::preset_rule::uniform_override::UniformOverride;
+use crate::context::forecast::Forecast;
+use crate::util::gw2_keybinds::KeybindClash;
+use crate::util::reshade::ini_detection::ReshadeIniCandidate;
+use crate::util::reshade::shortcut_repair::ShortcutRepair;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 pub struct UiContext {
@@ -7,6 +13,14 @@ pub struct UiContext {
     pub map_search_term: String,
     pub blacklist_map_search_term: String,
     pub invalid_reshade_preset_configuration: bool,
+    pub forecast: Option<Forecast>,
+    pub shortcut_repair_preview: Option<ShortcutRepair>,
+    pub shortcut_repair_backup_path: Option<PathBuf>,
+    pub discovered_presets: Option<Vec<(PathBuf, bool)>>,
+    pub compared_presets: (Option<PathBuf>, Option<PathBuf>),
+    pub reshade_ini_candidates: Option<Vec<ReshadeIniCandidate>>,
+    pub keybind_clashes: Option<(usize, Vec<KeybindClash>)>,
+    pub uniform_override_draft: UniformOverride,
 }
 
 #[derive(Clone, Debug)]
@@ -20,6 +34,14 @@ impl Default for UiContext {
             map_search_term: "".to_string(),
             blacklist_map_search_term: "".to_string(),
             invalid_reshade_preset_configuration: false,
+            forecast: None,
+            shortcut_repair_preview: None,
+            shortcut_repair_backup_path: None,
+            discovered_presets: None,
+            compared_presets: (None, None),
+            reshade_ini_candidates: None,
+            keybind_clashes: None,
+            uniform_override_draft: UniformOverride::default(),
         }
     }
 }
@@ -1,10 +1,17 @@
 use crate::addon::Addon;
+use crate::config::time_period_config::DEFAULT_CLOCK_OFFSET_SECONDS;
+use crate::context::time_period::CurrentTimePeriod;
 use crate::render::options::{ERROR_COLOR, SUCCESS_COLOR};
-use crate::render::util::shorten_path;
 use crate::render::util::ui::extended::UiExtended;
-use crate::thread::select_reshade_ini_file_thread;
+use crate::render::util::{preset_name, shorten_path};
+use crate::thread::{
+    check_keybind_clashes_thread, detect_reshade_ini_files_thread, select_reshade_ini_file_thread,
+};
+use crate::util::reshade::shortcut_repair::ShortcutRepair;
+use crate::util::reshade::switch_backend::SwitchBackendKind;
 use arboard::Clipboard;
-use nexus::imgui::{TreeNodeFlags, Ui};
+use log::error;
+use nexus::imgui::{Selectable, Slider, SliderFlags, TreeNodeFlags, Ui};
 
 impl Addon {
     pub fn render_configuration(&mut self, ui: &Ui) {
@@ -22,7 +29,7 @@ impl Addon {
                         ERROR_COLOR,
                         "Configure keybinds for the presets in ReShade:",
                     );
-                    ui.text_disabled("1. Right-click a preset name in the preset list and choose a key.\n2. Switch to different preset in ReShade manually to save the changes.");
+                    ui.text_disabled("1. Right-click a preset name in the preset list and choose a key.\n2. Switch to different preset in ReShade manually to save the changes.\nAlternatively, use 'Find presets without keybinds' to assign them automatically.");
                     ui.spacing();
                 }
                 if self.context.ui.invalid_reshade_preset_configuration {
@@ -32,11 +39,16 @@ impl Addon {
                         This may happen when presets are renamed without removing keybinds.\n\
                         Presets will not switch correctly if keybinds overlap.",
                     );
-                    ui.text_disabled("To fix an issue:\n\
-                        1. Press 'Copy to clipboard' button to copy valid configuration.\n\
-                        2. Exit the game.\n\
-                        3. Open ReShade.ini and replace 'PresetShortcutKeys' and 'PresetShortcutPaths' with copied values."
-                    );
+                    if ui.button("Repair keybinds") {
+                        match ShortcutRepair::prepare(
+                            &self.config.reshade.ini_path,
+                            &self.context.reshade,
+                        ) {
+                            Ok(repair) => self.context.ui.shortcut_repair_preview = Some(repair),
+                            Err(e) => error!("Could not read ReShade.ini: {}", e),
+                        }
+                    }
+                    ui.same_line();
                     if ui.button("Copy to clipboard") {
                         let mut clipboard = Clipboard::new().unwrap();
                         clipboard
@@ -45,19 +57,258 @@ impl Addon {
                     }
                     ui.spacing();
                 }
+                self.render_shortcut_repair(ui);
+                self.render_preset_discovery(ui);
+                ui.spacing();
+                self.render_keybind_clashes(ui);
+                ui.spacing();
             }
 
             ui.selected_file("ReShade.ini location", "##reshade_ini", &mut path, || {
                 select_reshade_ini_file_thread()
             });
+            if let Some(last_change) = self.context.reshade.ini_watch.last_change {
+                ui.text_disabled(format!(
+                    "Last changed: {}",
+                    last_change.format("%Y-%m-%d %H:%M:%S")
+                ));
+            }
+            self.render_reshade_ini_detection(ui);
+            ui.new_line();
+            self.render_switch_backend(ui);
             ui.new_line();
-            if self.context.links.rtapi.is_some() {
-                ui.text_colored(SUCCESS_COLOR, "RealTime API enabled.");
+            self.render_time_period_detection(ui);
+            ui.new_line();
+        }
+    }
+
+    fn render_reshade_ini_detection(&mut self, ui: &Ui) {
+        if ui.button("Detect in game directory") {
+            detect_reshade_ini_files_thread();
+        }
+        let Some(candidates) = &self.context.ui.reshade_ini_candidates else {
+            return;
+        };
+        if candidates.is_empty() {
+            ui.text_disabled("No ReShade configuration found in game directory.");
+            return;
+        }
+        for (i, candidate) in candidates.iter().enumerate() {
+            if candidate.path == self.config.reshade.ini_path {
+                ui.text_colored(SUCCESS_COLOR, "[in use]");
+            } else if ui.button(format!("Use this##reshade_ini{}", i)) {
+                self.config.reshade.ini_path = candidate.path.clone();
+            }
+            ui.same_line();
+            ui.text(shorten_path(candidate.path.display().to_string()));
+            if candidate.valid() {
+                ui.text_disabled(candidate.summary());
             } else {
-                ui.text("RealTime API disabled.");
-                ui.text_disabled("Install RealTime API for better map time detection in homestead and some map instances.");
+                ui.text_colored(ERROR_COLOR, candidate.summary());
+            }
+        }
+    }
+
+    fn render_switch_backend(&mut self, ui: &Ui) {
+        ui.header("Preset switching");
+        let reshade_config = &mut self.config.reshade;
+        if let Some(_c) = ui.begin_combo(
+            "Switch method##switch_backend",
+            reshade_config.switch_backend.to_string(),
+        ) {
+            for kind in SwitchBackendKind::ALL {
+                if Selectable::new(kind.to_string())
+                    .selected(kind == reshade_config.switch_backend)
+                    .build(ui)
+                {
+                    reshade_config.switch_backend = kind;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(kind.description());
+                }
+            }
+        }
+        ui.text_disabled(reshade_config.switch_backend.description());
+        match &self.context.reshade.reload_key {
+            Some(reload_key) => ui.text_disabled(format!("ReShade reload key: {}", reload_key)),
+            None => ui.text_disabled(
+                "Assign a reload key in ReShade settings to switch presets without keybinds.",
+            ),
+        }
+        if reshade_config.switch_backend != SwitchBackendKind::DryRun {
+            ui.text_disabled(
+                "Other methods are used for presets the selected one cannot switch to.",
+            );
+        }
+    }
+
+    fn render_keybind_clashes(&self, ui: &Ui) {
+        if ui.button("Check GW2 keybinds") {
+            check_keybind_clashes_thread();
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Compares preset keybinds with keybinds exported in GW2 options (Documents\\Guild Wars 2\\InputBinds).");
+        }
+        let Some((checked_files, clashes)) = &self.context.ui.keybind_clashes else {
+            return;
+        };
+        if *checked_files == 0 {
+            ui.text_disabled(
+                "No exported GW2 keybinds found. Export them in GW2 options under Control Options.",
+            );
+        } else if clashes.is_empty() {
+            ui.text_colored(
+                SUCCESS_COLOR,
+                format!("No clashes in {} GW2 keybind files.", checked_files),
+            );
+        }
+        for clash in clashes {
+            let clash_kind = if clash.is_exact() {
+                "triggers"
+            } else {
+                "may trigger"
+            };
+            ui.text_colored(
+                ERROR_COLOR,
+                format!(
+                    "{} ({}) {} '{}' ({}) in {}",
+                    preset_name(&clash.preset_path),
+                    clash.preset_key_combination,
+                    clash_kind,
+                    clash.action,
+                    clash.gw2_key_combination,
+                    clash.input_binds_file
+                ),
+            );
+        }
+    }
+
+    pub fn render_shortcut_repair(&mut self, ui: &Ui) {
+        if let Some(repair) = &self.context.ui.shortcut_repair_preview {
+            ui.text("Changes to ReShade.ini:");
+            if repair.changes.is_empty() {
+                ui.text_disabled("Nothing to change.");
+            }
+            for change in &repair.changes {
+                if let Some(old_value) = &change.old_value {
+                    ui.text_disabled(format!("- {}={}", change.key, old_value));
+                }
+                ui.text_colored(
+                    SUCCESS_COLOR,
+                    format!("+ {}={}", change.key, change.new_value),
+                );
+            }
+            ui.text_disabled(
+                "A backup is saved next to ReShade.ini before writing.\n\
+                Changes ReShade saves over are written again, restart the game after applying.",
+            );
+            if !repair.changes.is_empty() && ui.button("Apply changes") {
+                match repair.apply() {
+                    Ok(backup_path) => {
+                        self.context.reshade.applied_shortcut_repair = Some(repair.clone());
+                        self.context.reshade.shortcut_repair_overwritten = false;
+                        self.context.reshade.shortcut_repair_reapplied = 0;
+                        self.context.ui.shortcut_repair_backup_path = Some(backup_path);
+                        self.context.ui.shortcut_repair_preview = None;
+                    }
+                    Err(e) => error!("Could not repair ReShade.ini: {}", e),
+                }
+                return;
+            }
+            ui.same_line();
+            if ui.button("Cancel##shortcut_repair") {
+                self.context.ui.shortcut_repair_preview = None;
+            }
+            ui.spacing();
+        }
+        if self.context.reshade.shortcut_repair_overwritten {
+            ui.text_colored(
+                ERROR_COLOR,
+                "ReShade kept overwriting the keybind changes with its previous settings.\n\
+                Apply them again and restart the game before any preset is switched.",
+            );
+            ui.spacing();
+        } else if let Some(backup_path) = &self.context.ui.shortcut_repair_backup_path {
+            ui.text_colored(SUCCESS_COLOR, "Keybinds updated.");
+            if self.context.reshade.shortcut_repair_reapplied > 0 {
+                ui.text_colored(
+                    ERROR_COLOR,
+                    format!(
+                        "ReShade saved its previous keybinds over the changes, applied them again ({}x).\n\
+                        Restart the game so ReShade loads them.",
+                        self.context.reshade.shortcut_repair_reapplied
+                    ),
+                );
+            }
+            ui.text_disabled(format!(
+                "Backup: {}",
+                shorten_path(backup_path.display().to_string())
+            ));
+            ui.spacing();
+        }
+    }
+
+    fn render_time_period_detection(&mut self, ui: &Ui) {
+        ui.header("Time of day detection");
+        if self.context.links.rtapi.is_some() {
+            ui.text_colored(SUCCESS_COLOR, "RealTime API enabled.");
+        } else {
+            ui.text("RealTime API disabled.");
+            ui.text_disabled("Install RealTime API for better map time detection in homestead and some map instances.");
+        }
+        let time_period_config = &mut self.config.time_period;
+        ui.checkbox(
+            "Use RealTime API##time_period",
+            &mut time_period_config.use_real_time_api,
+        );
+        ui.checkbox("Use clock##time_period", &mut time_period_config.use_clock);
+        let mut offset_minutes = time_period_config.clock_offset_seconds as f32 / 60.0;
+        if Slider::new("Clock offset (min)##time_period", 0.0, 120.0f32)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .display_format("%.1f")
+            .build(ui, &mut offset_minutes)
+        {
+            time_period_config.clock_offset_seconds = (offset_minutes * 60.0) as i64;
+        }
+        if ui.button("It is dusk right now") {
+            let current_map_id = self.context.previous_map_id.unwrap_or_default();
+            if let Some(offset) = self.context.dusk_calibration_offset(&current_map_id) {
+                time_period_config.clock_offset_seconds = offset;
+            }
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Calibrates clock offset so that dusk starts now on the current map.");
+        }
+        ui.same_line();
+        if ui.button("Reset offset") {
+            time_period_config.clock_offset_seconds = DEFAULT_CLOCK_OFFSET_SECONDS;
+        }
+        Slider::new("Lead time (s)##time_period", 0, 120u32)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut time_period_config.lead_time_seconds);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Switches to the next time period earlier to account for ReShade preset transition.");
+        }
+        if let Some(_c) = ui.begin_combo(
+            "Manual time period##time_period",
+            format!("{:?}", time_period_config.manual_time_period),
+        ) {
+            for time_period in CurrentTimePeriod::ALL {
+                if Selectable::new(format!("{:?}", time_period))
+                    .selected(time_period == time_period_config.manual_time_period)
+                    .build(ui)
+                {
+                    time_period_config.manual_time_period = time_period;
+                }
             }
-            ui.new_line();
         }
+        ui.text_disabled("Sources are used in order: RealTime API, clock, manual.");
+        ui.spacing();
+        ui.text(format!(
+            "Current time period: {:?} (source: {}, confidence: {})",
+            self.context.current_time_period,
+            self.context.time_period_source,
+            self.context.time_period_source.confidence()
+        ));
     }
 }
@@ -0,0 +1,88 @@
+use crate::addon::Addon;
+use crate::context::forecast::FORECAST_HOURS;
+use crate::render::options::ERROR_COLOR;
+use crate::render::util::{format_minute_of_day, preset_name};
+use chrono::Local;
+use nexus::imgui::{TreeNodeFlags, Ui};
+
+impl Addon {
+    pub fn render_forecast(&mut self, ui: &Ui) {
+        if ui.collapsing_header("Forecast##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            let current_map_id = self.context.previous_map_id.unwrap_or_default();
+            let refresh = ui.button("Refresh##forecast");
+            if refresh
+                || self
+                    .context
+                    .ui
+                    .forecast
+                    .as_ref()
+                    .map(|forecast| forecast.is_outdated(&current_map_id))
+                    .unwrap_or(true)
+            {
+                self.context.ui.forecast = Some(self.context.forecast(
+                    &current_map_id,
+                    &self.config.time_period,
+                    &self.config.preset_rules,
+                ));
+            }
+            ui.same_line();
+            let map_name = self
+                .context
+                .ui
+                .map_names
+                .get(&current_map_id.to_string())
+                .cloned()
+                .unwrap_or(current_map_id.to_string());
+            ui.text_disabled(format!("Next {} hours on {}", FORECAST_HOURS, map_name));
+            if let Some(forecast) = &self.context.ui.forecast {
+                if let Some(_t) = ui.begin_table("forecast", 4) {
+                    for segment in &forecast.segments {
+                        let evaluation_time = &segment.evaluation_time;
+                        ui.table_next_column();
+                        ui.text(format!(
+                            "{}",
+                            evaluation_time.time.with_timezone(&Local).format("%H:%M")
+                        ));
+                        ui.table_next_column();
+                        ui.text_disabled(format!(
+                            "{:?} ({})",
+                            evaluation_time.time_period,
+                            format_minute_of_day(evaluation_time.game_clock_minute)
+                        ));
+                        ui.table_next_column();
+                        match segment
+                            .rule_index
+                            .and_then(|rule_index| self.config.preset_rules.get(rule_index))
+                        {
+                            Some(rule) => {
+                                ui.text(&rule.rule_name);
+                                let possible_rule_names: Vec<&str> = segment
+                                    .possible_rule_indices
+                                    .iter()
+                                    .filter_map(|rule_index| {
+                                        self.config.preset_rules.get(*rule_index)
+                                    })
+                                    .map(|rule| rule.rule_name.as_str())
+                                    .collect();
+                                if !possible_rule_names.is_empty() && ui.is_item_hovered() {
+                                    ui.tooltip_text(format!(
+                                        "By chance: {}",
+                                        possible_rule_names.join(", ")
+                                    ));
+                                }
+                                ui.table_next_column();
+                                ui.text_disabled(preset_name(&rule.preset_path));
+                            }
+                            None => {
+                                ui.text_colored(ERROR_COLOR, "[no rule]");
+                                ui.table_next_column();
+                            }
+                        }
+                    }
+                }
+            }
+            ui.text_disabled("Local time of each time period change. Speed conditions are evaluated as of now, rules depending on chance are listed on hover.");
+            ui.new_line();
+        }
+    }
+}
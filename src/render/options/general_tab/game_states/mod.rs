@@ -0,0 +1,61 @@
+use crate::addon::Addon;
+use crate::context::game_state::GameState;
+use crate::render::util::preset_name;
+use nexus::imgui::{Selectable, TreeNodeFlags, Ui};
+use std::path::PathBuf;
+
+impl Addon {
+    pub fn render_game_states(&mut self, ui: &Ui) {
+        if ui.collapsing_header("Game states##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            ui.text_disabled("Presets to activate outside of gameplay. If none is selected, current preset is kept.");
+            ui.spacing();
+            let mut presets: Vec<PathBuf> = self
+                .context
+                .reshade
+                .preset_shortcuts
+                .right_values()
+                .cloned()
+                .collect();
+            presets.sort();
+            for game_state in [GameState::CharacterSelect, GameState::LoadingScreen] {
+                if let Some(preset_path) = self.config.game_state_presets.get_mut(&game_state) {
+                    Self::render_game_state_preset(&game_state, preset_path, &presets, ui);
+                }
+            }
+            ui.spacing();
+            ui.text_disabled(format!(
+                "Current game state: {}",
+                self.context.game_state.current
+            ));
+            ui.new_line();
+        }
+    }
+
+    fn render_game_state_preset(
+        game_state: &GameState,
+        preset_path: &mut Option<PathBuf>,
+        presets: &[PathBuf],
+        ui: &Ui,
+    ) {
+        let preview = preset_path
+            .as_ref()
+            .map(|path| preset_name(path))
+            .unwrap_or("None".to_string());
+        if let Some(_c) = ui.begin_combo(format!("{}##game_state_preset", game_state), preview) {
+            if Selectable::new("None")
+                .selected(preset_path.is_none())
+                .build(ui)
+            {
+                *preset_path = None;
+            }
+            for (i, preset) in presets.iter().enumerate() {
+                if Selectable::new(format!("{}##{}{}", preset_name(preset), game_state, i))
+                    .selected(preset_path.as_ref() == Some(preset))
+                    .build(ui)
+                {
+                    *preset_path = Some(preset.clone());
+                }
+            }
+        }
+    }
+}
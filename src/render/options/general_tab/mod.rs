@@ -1,10 +1,15 @@
 mod configuration;
+mod forecast;
+mod game_states;
+mod preset_diff;
+mod preset_discovery;
 mod rule_edit;
 
 use crate::addon::Addon;
 use crate::config::preset_rule::{PresetRule, RuleValidationError};
 use crate::context::ui::UiContext;
 use crate::render::options::{ERROR_COLOR, SUCCESS_COLOR};
+use crate::render::util::preset_name;
 use crate::render::util::ui::extended::UiExtended;
 use crate::render::util::ui::{process_ui_actions_for_vec, UiAction};
 use nexus::imgui::{MenuItem, TreeNodeFlags, Ui};
@@ -14,9 +19,14 @@ impl Addon {
         ui.spacing();
         if let Some(rule_under_edit_index) = &self.context.ui.rule_under_edit_index {
             self.render_rule_edit(*rule_under_edit_index, ui);
+            //rules may change while editing
+            self.context.ui.forecast = None;
         } else {
             if self.config.valid() && self.context.valid() {
                 self.render_rules(ui);
+                self.render_forecast(ui);
+                self.render_preset_comparison(ui);
+                self.render_game_states(ui);
             }
             self.render_configuration(ui);
             self.render_how_to_use(ui);
@@ -61,6 +71,17 @@ impl Addon {
                 ui.move_down_button(&mut ui_actions, rule_index, last_rule_index);
                 ui.table_next_column();
                 ui.text(&rule.rule_name);
+                if ui.is_item_hovered() {
+                    if let Some(technique_summary) =
+                        self.context.reshade.technique_summary(&rule.preset_path)
+                    {
+                        ui.tooltip_text(format!(
+                            "{}: {}",
+                            preset_name(&rule.preset_path),
+                            technique_summary
+                        ));
+                    }
+                }
                 ui.table_next_column();
                 if let Some(key_combination) = self
                     .context
@@ -68,10 +89,16 @@ impl Addon {
                     .preset_shortcuts
                     .get_by_right(&rule.preset_path)
                 {
-                    ui.text_disabled(format!("{}", key_combination));
+                    if key_combination.is_valid() {
+                        ui.text_disabled(format!("{}", key_combination));
+                    } else {
+                        ui.text_colored(ERROR_COLOR, format!("{}", key_combination));
+                    }
                 }
                 ui.table_next_column();
-                if let Err(RuleValidationError::NoPresetSelected) = rule.validate() {
+                if let Err(RuleValidationError::NoPresetSelected) =
+                    rule.validate(&self.context.reshade)
+                {
                     ui.text_colored(ERROR_COLOR, "[invalid preset]");
                     ui.same_line();
                 } else if !self
@@ -133,7 +160,7 @@ impl Addon {
                 1. Rules are processed from top to bottom.\n\
                 2. First successful rule is activated.\n\
                 3. If there are no matching rules, last rule on the list is used by default. Therefore conditions on a default rule may be empty.\n\
-                4. On character select, default rule will be used."
+                4. On character select and loading screens, preset configured in 'Game states' is used. If there is none, current preset is kept."
             );
         }
     }
@@ -0,0 +1,96 @@
+use crate::addon::Addon;
+use crate::context::reshade_context::preset::diff::PresetDiff;
+use crate::render::options::{ERROR_COLOR, SUCCESS_COLOR};
+use crate::render::util::preset_name;
+use nexus::imgui::{Selectable, TreeNodeFlags, Ui};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_VALUE: &str = "(default)";
+
+impl Addon {
+    pub fn render_preset_comparison(&mut self, ui: &Ui) {
+        if ui.collapsing_header("Preset comparison##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            let mut presets: Vec<PathBuf> = self
+                .context
+                .reshade
+                .preset_shortcuts
+                .right_values()
+                .cloned()
+                .collect();
+            presets.sort();
+            let (from, to) = &mut self.context.ui.compared_presets;
+            render_preset_combo("From##preset_diff", from, &presets, ui);
+            render_preset_combo("To##preset_diff", to, &presets, ui);
+            if let (Some(from), Some(to)) = (from, to) {
+                match (
+                    self.context.reshade.presets.get(from),
+                    self.context.reshade.presets.get(to),
+                ) {
+                    (Some(from_preset), Some(to_preset)) => {
+                        render_preset_diff(&from_preset.diff(to_preset), from, to, ui)
+                    }
+                    _ => ui.text_colored(ERROR_COLOR, "Preset files could not be read"),
+                }
+            }
+            ui.new_line();
+        }
+    }
+}
+
+fn render_preset_combo(label: &str, selected: &mut Option<PathBuf>, presets: &[PathBuf], ui: &Ui) {
+    let preview = selected
+        .as_ref()
+        .map(|preset| preset_name(preset))
+        .unwrap_or("Select preset".to_string());
+    if let Some(_c) = ui.begin_combo(label, preview) {
+        for (i, preset) in presets.iter().enumerate() {
+            if Selectable::new(format!("{}##{}{}", preset_name(preset), label, i))
+                .selected(selected.as_ref() == Some(preset))
+                .build(ui)
+            {
+                *selected = Some(preset.clone());
+            }
+        }
+    }
+}
+
+pub fn render_preset_diff(diff: &PresetDiff, from: &Path, to: &Path, ui: &Ui) {
+    if diff.is_empty() {
+        ui.text_disabled("Presets are identical.");
+        return;
+    }
+    for technique in &diff.removed_techniques {
+        ui.text_disabled(format!("- {}", technique));
+    }
+    for technique in &diff.added_techniques {
+        ui.text_colored(SUCCESS_COLOR, format!("+ {}", technique));
+    }
+    if diff.changed_values.is_empty() {
+        return;
+    }
+    ui.spacing();
+    if let Some(_t) = ui.begin_table("preset_diff", 3) {
+        ui.table_next_column();
+        ui.text_disabled("Value");
+        ui.table_next_column();
+        ui.text_disabled(preset_name(from));
+        ui.table_next_column();
+        ui.text_disabled(preset_name(to));
+        let mut current_effect_file = None;
+        for change in &diff.changed_values {
+            if current_effect_file != Some(&change.effect_file) {
+                current_effect_file = Some(&change.effect_file);
+                ui.table_next_column();
+                ui.text(&change.effect_file);
+                ui.table_next_column();
+                ui.table_next_column();
+            }
+            ui.table_next_column();
+            ui.text(format!("  {}", change.key));
+            ui.table_next_column();
+            ui.text_disabled(change.from.as_deref().unwrap_or(DEFAULT_VALUE));
+            ui.table_next_column();
+            ui.text(change.to.as_deref().unwrap_or(DEFAULT_VALUE));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::addon::Addon;
+use crate::render::util::preset_name;
+use crate::util::ini::Ini;
+use crate::util::reshade::preset_discovery::{assign_key_combinations, discover_presets};
+use crate::util::reshade::shortcut_repair::ShortcutRepair;
+use log::error;
+use nexus::imgui::Ui;
+use std::path::PathBuf;
+
+impl Addon {
+    pub fn render_preset_discovery(&mut self, ui: &Ui) {
+        if ui.button("Find presets without keybinds") {
+            match Ini::read(&self.config.reshade.ini_path) {
+                Ok(reshade_ini) => {
+                    self.context.ui.discovered_presets = Some(
+                        discover_presets(&reshade_ini, &self.context.reshade)
+                            .into_iter()
+                            .map(|preset| (preset, false))
+                            .collect(),
+                    );
+                }
+                Err(e) => error!("Could not read ReShade.ini: {}", e),
+            }
+        }
+        let Some(discovered_presets) = &mut self.context.ui.discovered_presets else {
+            return;
+        };
+        if discovered_presets.is_empty() {
+            ui.text_disabled("All presets already have keybinds.");
+            return;
+        }
+        ui.text_disabled("Select presets to manage, keybinds are assigned automatically:");
+        for (i, (preset, selected)) in discovered_presets.iter_mut().enumerate() {
+            ui.checkbox(
+                format!("{}##discovered{}", preset_name(preset), i),
+                selected,
+            );
+            if ui.is_item_hovered() {
+                ui.tooltip_text(preset.display().to_string());
+            }
+        }
+        let selected_presets: Vec<PathBuf> = discovered_presets
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(preset, _)| preset.clone())
+            .collect();
+        if !selected_presets.is_empty() && ui.button("Assign keybinds") {
+            self.prepare_key_assignment(&selected_presets);
+        }
+    }
+
+    pub fn prepare_key_assignment(&mut self, presets: &[PathBuf]) {
+        let ini_path = &self.config.reshade.ini_path;
+        let reshade_ini = match Ini::read(ini_path) {
+            Ok(reshade_ini) => reshade_ini,
+            Err(e) => {
+                error!("Could not read ReShade.ini: {}", e);
+                return;
+            }
+        };
+        let assignments = assign_key_combinations(presets, &reshade_ini);
+        if assignments.len() < presets.len() {
+            error!(
+                "No free keybinds left for {} presets",
+                presets.len() - assignments.len()
+            );
+        }
+        match ShortcutRepair::prepare_with_assignments(
+            ini_path,
+            &self.context.reshade,
+            &assignments,
+        ) {
+            Ok(repair) => {
+                self.context.ui.shortcut_repair_preview = Some(repair);
+                self.context.ui.discovered_presets = None;
+            }
+            Err(e) => error!("Could not read ReShade.ini: {}", e),
+        }
+    }
+}
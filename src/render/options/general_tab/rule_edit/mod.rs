@@ -1,19 +1,36 @@
 use crate::addon::Addon;
+use crate::config::preset_rule::rule_condition::condition_data::date_range::{DateRange, MonthDay};
+use crate::config::preset_rule::rule_condition::condition_data::local_time_condition::{
+    local_minute_of_day, LocalTimeCondition,
+};
+use crate::config::preset_rule::rule_condition::condition_data::meta_event_condition::MetaEventCondition;
+use crate::config::preset_rule::rule_condition::condition_data::speed_range::{
+    SpeedRange, MAX_HORIZONTAL_SPEED, MAX_VERTICAL_SPEED,
+};
 use crate::config::preset_rule::rule_condition::condition_data::time_periods::TimePeriods;
 use crate::config::preset_rule::rule_condition::condition_data::ConditionData;
 use crate::config::preset_rule::rule_condition::conjunction_type::ConjunctionType;
 use crate::config::preset_rule::rule_condition::RuleCondition;
+use crate::config::preset_rule::uniform_override::UniformOverride;
 use crate::config::preset_rule::PresetRule;
-use crate::config::SwitchValue;
+use crate::config::{ReshadeConfig, SwitchValue};
+use crate::context::festival::{load_festivals, Festival};
+use crate::context::meta_event::{load_meta_events, MetaEvent};
+use crate::context::movement::MovementContext;
+use crate::context::reshade_context::preset::Technique;
 use crate::context::reshade_context::ReshadeContext;
 use crate::context::Context;
-use crate::render::options::ERROR_COLOR;
+use crate::render::options::general_tab::preset_diff::render_preset_diff;
+use crate::render::options::{ERROR_COLOR, SUCCESS_COLOR};
 use crate::render::util::ui::extended::UiExtended;
 use crate::render::util::ui::{process_ui_actions_for_vec, RenderResult, UiAction};
+use crate::render::util::{format_minute_of_day, preset_name, shorten_path};
+use crate::util::reshade::transition::record_original_transition_duration;
+use chrono::{Datelike, Local, Utc};
 use function_name::named;
 use log::error;
 use nexus::data_link::mumble::MumblePtr;
-use nexus::imgui::{TreeNodeFlags, Ui};
+use nexus::imgui::{Selectable, Slider, TreeNodeFlags, Ui};
 use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::path::PathBuf;
@@ -33,6 +50,20 @@ impl Addon {
             ui.new_line();
             Self::render_activation_conditions(&mut self.context, rule, ui);
             Self::render_preset_picker(&self.context.reshade, rule, ui);
+            Self::render_technique_toggles(&self.context.reshade, rule, ui);
+            Self::render_uniform_overrides(
+                &self.context.reshade,
+                &mut self.context.ui.uniform_override_draft,
+                rule,
+                ui,
+            );
+            let transition_duration_recorded =
+                Self::render_transition_duration(&mut self.config.reshade, rule, ui);
+            Self::render_preset_changes(&self.context.reshade, rule, ui);
+            if transition_duration_recorded {
+                self.config.save();
+            }
+            self.render_preset_variant(rule_index, ui);
             Self::render_additional_info(&self.context.links.mumble, ui);
             ui.spacing();
         } else {
@@ -55,6 +86,246 @@ impl Addon {
         }
     }
 
+    fn render_technique_toggles(reshade_context: &ReshadeContext, rule: &mut PresetRule, ui: &Ui) {
+        if ui.collapsing_header("Techniques##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            let Some(preset) = reshade_context
+                .presets
+                .get(&reshade_context.resolve(&rule.preset_path))
+            else {
+                ui.text_disabled("Select a preset with a keybind to toggle its techniques.");
+                ui.new_line();
+                return;
+            };
+            ui.text_disabled(
+                "Turns techniques on or off on top of the selected preset.\n\
+                The changes are saved to a variant of the preset which is activated instead.",
+            );
+            let enabled_techniques: Vec<String> = preset
+                .techniques
+                .iter()
+                .map(|technique| technique.to_reshade_value())
+                .collect();
+            let mut techniques: Vec<String> = if preset.technique_sorting.is_empty() {
+                enabled_techniques.clone()
+            } else {
+                preset
+                    .technique_sorting
+                    .iter()
+                    .map(|technique| technique.to_reshade_value())
+                    .collect()
+            };
+            for technique in rule.technique_toggles.keys() {
+                if !techniques.contains(technique) {
+                    techniques.push(technique.clone());
+                }
+            }
+            for technique in techniques {
+                let enabled_in_preset = enabled_techniques.contains(&technique);
+                let mut enabled = rule
+                    .technique_toggles
+                    .get(&technique)
+                    .copied()
+                    .unwrap_or(enabled_in_preset);
+                if ui.checkbox(
+                    format!("{}##technique_toggle", Technique::parse(&technique)),
+                    &mut enabled,
+                ) {
+                    if enabled == enabled_in_preset {
+                        rule.technique_toggles.remove(&technique);
+                    } else {
+                        rule.technique_toggles.insert(technique.clone(), enabled);
+                    }
+                }
+                if rule.technique_toggles.contains_key(&technique) {
+                    ui.same_line();
+                    ui.text_colored(SUCCESS_COLOR, "changed");
+                }
+            }
+            if !rule.technique_toggles.is_empty() && ui.button("Reset techniques") {
+                rule.technique_toggles.clear();
+            }
+            ui.new_line();
+        }
+    }
+
+    fn render_uniform_overrides(
+        reshade_context: &ReshadeContext,
+        draft: &mut UniformOverride,
+        rule: &mut PresetRule,
+        ui: &Ui,
+    ) {
+        if ui.collapsing_header("Uniform overrides##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            ui.text_disabled(
+                "Replaces uniform values of the selected preset, e.g. Tonemap.fx: Exposure=-0.2.\n\
+                The changes are saved to a variant of the preset which is activated instead.",
+            );
+            let mut removed_index = None;
+            for (i, uniform_override) in rule.uniform_overrides.iter_mut().enumerate() {
+                if ui.small_button(format!("x##uniform_override{}", i)) {
+                    removed_index = Some(i);
+                }
+                ui.same_line();
+                ui.input_text(
+                    format!(
+                        "{}: {}##uniform_override{}",
+                        uniform_override.effect_file, uniform_override.key, i
+                    ),
+                    &mut uniform_override.value,
+                )
+                .build();
+            }
+            if let Some(i) = removed_index {
+                rule.uniform_overrides.remove(i);
+            }
+
+            let Some(preset) = reshade_context
+                .presets
+                .get(&reshade_context.resolve(&rule.preset_path))
+            else {
+                ui.text_disabled("Select a preset with a keybind to override its uniforms.");
+                ui.new_line();
+                return;
+            };
+            if let Some(_c) = ui.begin_combo("Effect##uniform_override", &draft.effect_file) {
+                for effect in &preset.effects {
+                    if Selectable::new(&effect.effect_file)
+                        .selected(effect.effect_file == draft.effect_file)
+                        .build(ui)
+                    {
+                        draft.effect_file = effect.effect_file.clone();
+                        draft.key.clear();
+                        draft.value.clear();
+                    }
+                }
+            }
+            if let Some(effect) = preset.effect(&draft.effect_file) {
+                if let Some(_c) = ui.begin_combo("Uniform##uniform_override", &draft.key) {
+                    for (key, value) in &effect.values {
+                        if Selectable::new(key).selected(*key == draft.key).build(ui) {
+                            draft.key = key.clone();
+                            draft.value = value.clone();
+                        }
+                    }
+                }
+            }
+            ui.input_text("Value##uniform_override", &mut draft.value)
+                .build();
+            let exists = rule.uniform_overrides.iter().any(|uniform_override| {
+                uniform_override.effect_file == draft.effect_file
+                    && uniform_override.key == draft.key
+            });
+            if !draft.key.is_empty() && !exists && ui.button("Add override") {
+                rule.uniform_overrides.push(draft.clone());
+                *draft = UniformOverride::default();
+            }
+            ui.new_line();
+        }
+    }
+
+    fn render_preset_variant(&mut self, rule_index: usize, ui: &Ui) {
+        let Some(rule) = self.config.preset_rules.get(rule_index).cloned() else {
+            return;
+        };
+        if !rule.has_variant() {
+            return;
+        }
+        let variant_path = rule.activation_preset_path(&self.context.reshade);
+        if ui.collapsing_header(
+            "Preset variant##rps",
+            TreeNodeFlags::SPAN_AVAIL_WIDTH | TreeNodeFlags::DEFAULT_OPEN,
+        ) {
+            ui.text(format!("Variant: {}", preset_name(&variant_path)));
+            ui.text_disabled(shorten_path(variant_path.display().to_string()));
+            ui.text_disabled(
+                "Generated from the base preset and updated when the base preset changes.",
+            );
+            let key_combination = self
+                .context
+                .reshade
+                .preset_shortcuts
+                .get_by_right(&variant_path)
+                .cloned();
+            match key_combination {
+                Some(key_combination) => ui.text_disabled(format!("Keybind: {}", key_combination)),
+                None => {
+                    ui.text_colored(
+                        ERROR_COLOR,
+                        "No keybind, the variant is switched through ReShade.ini and the ReShade reload key.",
+                    );
+                    if ui.button("Generate variant and assign keybind") {
+                        match rule.write_variant(&self.context.reshade) {
+                            Ok(variant_path) => self.prepare_key_assignment(&[variant_path]),
+                            Err(e) => error!("Could not write variant: {}", e),
+                        }
+                    }
+                }
+            }
+            self.render_shortcut_repair(ui);
+            ui.new_line();
+        }
+    }
+
+    fn render_transition_duration(
+        reshade_config: &mut ReshadeConfig,
+        rule: &mut PresetRule,
+        ui: &Ui,
+    ) -> bool {
+        let mut recorded = false;
+        if ui.collapsing_header("Transition##rps", TreeNodeFlags::SPAN_AVAIL_WIDTH) {
+            let mut custom_duration = rule.transition_duration_ms.is_some();
+            if ui.checkbox("Custom transition duration", &mut custom_duration) {
+                rule.transition_duration_ms = custom_duration.then_some(1000);
+                // Recorded before the first switch can override it.
+                recorded = custom_duration && record_original_transition_duration(reshade_config);
+            }
+            if let Some(transition_duration_ms) = &mut rule.transition_duration_ms {
+                Slider::new("Duration (ms)##transition", 0, 10000u32)
+                    .build(ui, transition_duration_ms);
+                if !reshade_config.switch_backend.applies_transition_duration() {
+                    ui.text_colored(
+                        ERROR_COLOR,
+                        format!(
+                            "Not applied with the \"{}\" switch method.",
+                            reshade_config.switch_backend
+                        ),
+                    );
+                }
+            }
+            ui.text_disabled(
+                "ReShade only reads the duration when reloading, so it is applied when switching\n\
+                with the \"ReShade.ini and reload\" method, also used for presets without keybinds.\n\
+                The duration configured in ReShade is used otherwise and restored when the addon is unloaded.",
+            );
+            ui.new_line();
+        }
+        recorded
+    }
+
+    fn render_preset_changes(reshade_context: &ReshadeContext, rule: &PresetRule, ui: &Ui) {
+        let active_preset_path = reshade_context.resolve(&reshade_context.active_preset_path);
+        let rule_preset_path = reshade_context.resolve(&rule.preset_path);
+        if rule_preset_path == active_preset_path {
+            return;
+        }
+        if let (Some(active_preset), Some(rule_preset)) = (
+            reshade_context.presets.get(&active_preset_path),
+            reshade_context.presets.get(&rule_preset_path),
+        ) {
+            if ui.collapsing_header(
+                "Changes from active preset##rps",
+                TreeNodeFlags::SPAN_AVAIL_WIDTH,
+            ) {
+                render_preset_diff(
+                    &active_preset.diff(rule_preset),
+                    &active_preset_path,
+                    &rule_preset_path,
+                    ui,
+                );
+                ui.new_line();
+            }
+        }
+    }
+
     fn render_preset_picker(reshade_context: &ReshadeContext, rule: &mut PresetRule, ui: &Ui) {
         if ui.collapsing_header(
             "Preset to activate##rps",
@@ -74,8 +345,18 @@ impl Addon {
             .auto_select_all(true)
             .read_only(true)
             .build();
-            if !rule.preset_path.exists() {
+            if !reshade_context.preset_exists(&rule.preset_path) {
                 ui.text_colored(ERROR_COLOR, "Invalid preset selected");
+            } else if let Some(technique_summary) =
+                reshade_context.technique_summary(&rule.preset_path)
+            {
+                ui.text_wrapped(format!("Techniques: {}", technique_summary));
+            }
+            if let Some(key_combination) = reshade_context
+                .preset_shortcuts
+                .get_by_right(&reshade_context.resolve(&rule.preset_path))
+            {
+                ui.text_disabled(format!("Keybind: {}", key_combination));
             }
             ui.text_disabled(
                 "For preset to be visible, make sure it has a key assigned in ReShade settings.\n\
@@ -104,6 +385,11 @@ impl Addon {
                     if ui.button(filename) {
                         rule.preset_path = preset_path.clone();
                     }
+                    if ui.is_item_hovered() {
+                        if let Some(preset) = reshade_context.presets.get(preset_path) {
+                            ui.tooltip_text(preset.technique_names().join("\n"));
+                        }
+                    }
                 } else {
                     error!(
                         "[{}] Could not parse filename for preset path [{:?}]",
@@ -169,52 +455,25 @@ impl Addon {
         ui: &Ui,
         rendered_conditions: HashSet<mem::Discriminant<ConditionData>>,
     ) {
-        if rendered_conditions.len() != 5 {
-            ui.new_line();
-            ui.separator();
-            ui.header("Add new condition:");
-
-            if !rendered_conditions
-                .contains(&mem::discriminant(&ConditionData::Maps(Default::default())))
-            {
-                if ui.button("Map") {
-                    rule.conditions.push(RuleCondition::new(
-                        ConditionData::Maps(Vec::new()),
-                        ConjunctionType::And,
-                    ))
-                }
-                ui.same_line();
-            }
-            if !rendered_conditions.contains(&mem::discriminant(&ConditionData::BlacklistedMaps(
-                Default::default(),
-            ))) {
-                if ui.button("Blacklisted map") {
-                    rule.conditions.push(RuleCondition::new(
-                        ConditionData::BlacklistedMaps(Vec::new()),
-                        ConjunctionType::And,
-                    ))
-                }
-                ui.same_line();
-            }
-            if !rendered_conditions
-                .contains(&mem::discriminant(&ConditionData::Time(Default::default())))
-            {
-                if ui.button("Time") {
-                    rule.conditions.push(RuleCondition::new(
-                        ConditionData::Time(TimePeriods::default()),
-                        ConjunctionType::And,
-                    ));
-                }
+        let new_conditions: Vec<ConditionData> = ConditionData::new_conditions()
+            .into_iter()
+            .filter(|condition_data| {
+                !rendered_conditions.contains(&mem::discriminant(condition_data))
+            })
+            .collect();
+        if new_conditions.is_empty() {
+            return;
+        }
+        ui.new_line();
+        ui.separator();
+        ui.header("Add new condition:");
+        for (i, condition_data) in new_conditions.into_iter().enumerate() {
+            if i > 0 {
                 ui.same_line();
             }
-            if !rendered_conditions.contains(&mem::discriminant(&ConditionData::Chance(
-                Default::default(),
-            ))) && ui.button("Chance")
-            {
-                rule.conditions.push(RuleCondition::new(
-                    ConditionData::Chance(0.0),
-                    ConjunctionType::And,
-                ));
+            if ui.button(condition_data.label()) {
+                rule.conditions
+                    .push(RuleCondition::new(condition_data, ConjunctionType::And));
             }
         }
     }
@@ -249,6 +508,25 @@ impl Addon {
             ConditionData::Chance(chance) => {
                 Self::render_chance_condition_data(chance, ui);
             }
+            ConditionData::Speed(speed_range) => {
+                Self::render_speed_condition_data(speed_range, &context.movement, ui);
+            }
+            ConditionData::GameClock { from, to } => {
+                Self::render_game_clock_condition_data(from, to, context.game_clock_minute, ui);
+            }
+            ConditionData::LocalTime(local_time_condition) => {
+                Self::render_local_time_condition_data(local_time_condition, ui);
+            }
+            ConditionData::Calendar(date_range) => {
+                Self::render_calendar_condition_data(date_range, &mut context.festivals, ui);
+            }
+            ConditionData::MetaEvent(meta_event_condition) => {
+                Self::render_meta_event_condition_data(
+                    meta_event_condition,
+                    &mut context.meta_events,
+                    ui,
+                );
+            }
         }
         rendered_conditions.insert(mem::discriminant(&rule_condition.data));
     }
@@ -410,4 +688,194 @@ impl Addon {
         ui.header("Activation chance:");
         ui.slider_percent("Chance (%)", chance);
     }
+
+    fn render_speed_condition_data(
+        speed_range: &mut SpeedRange,
+        movement: &MovementContext,
+        ui: &Ui,
+    ) {
+        ui.header("When moving at speed (m/s):");
+        Self::render_speed_slider(
+            "Min horizontal",
+            &mut speed_range.min_horizontal,
+            0.0,
+            MAX_HORIZONTAL_SPEED,
+            ui,
+        );
+        Self::render_speed_slider(
+            "Max horizontal",
+            &mut speed_range.max_horizontal,
+            0.0,
+            MAX_HORIZONTAL_SPEED,
+            ui,
+        );
+        Self::render_speed_slider(
+            "Min vertical",
+            &mut speed_range.min_vertical,
+            -MAX_VERTICAL_SPEED,
+            MAX_VERTICAL_SPEED,
+            ui,
+        );
+        Self::render_speed_slider(
+            "Max vertical",
+            &mut speed_range.max_vertical,
+            -MAX_VERTICAL_SPEED,
+            MAX_VERTICAL_SPEED,
+            ui,
+        );
+        ui.text_disabled(format!(
+            "Current speed: {:.1} horizontal, {:.1} vertical",
+            movement.horizontal_speed, movement.vertical_speed
+        ));
+        ui.text_disabled(
+            "Gliding usually means fast horizontal movement with negative vertical speed.",
+        );
+    }
+
+    fn render_speed_slider(label: &str, value: &mut f32, min: f32, max: f32, ui: &Ui) {
+        let display_format = if (*value <= min && min < 0.0) || *value >= max {
+            "No limit"
+        } else {
+            "%.1f"
+        };
+        Slider::new(format!("{}##speed", label), min, max)
+            .display_format(display_format)
+            .build(ui, value);
+    }
+
+    fn render_meta_event_condition_data(
+        meta_event_condition: &mut MetaEventCondition,
+        meta_events: &mut Vec<MetaEvent>,
+        ui: &Ui,
+    ) {
+        ui.header("When meta event is active or about to start:");
+        let preview = if meta_event_condition.event_name.is_empty() {
+            "Select meta event".to_string()
+        } else {
+            meta_event_condition.event_name.clone()
+        };
+        if let Some(_c) = ui.begin_combo("Meta event##meta_event", preview) {
+            for meta_event in meta_events.iter() {
+                if Selectable::new(&meta_event.name)
+                    .selected(meta_event.name == meta_event_condition.event_name)
+                    .build(ui)
+                {
+                    meta_event_condition.event_name = meta_event.name.clone();
+                }
+            }
+        }
+        Slider::new("Minutes before start##meta_event", 0, 60)
+            .build(ui, &mut meta_event_condition.minutes_before);
+
+        if let Some(meta_event) = meta_events
+            .iter()
+            .find(|meta_event| meta_event.name == meta_event_condition.event_name)
+        {
+            match meta_event.seconds_until_active(Utc::now()) {
+                Some(0) => ui.text_colored(SUCCESS_COLOR, "Active now"),
+                Some(seconds) => ui.text_disabled(format!("Starts in {} min", seconds / 60)),
+                None => ui.text_colored(ERROR_COLOR, "No valid start times in schedule"),
+            }
+        } else if !meta_event_condition.event_name.is_empty() {
+            ui.text_colored(ERROR_COLOR, "Meta event not found in schedule");
+        }
+        ui.text_disabled(
+            "Schedule (UTC) can be edited in meta_events.json in the addon directory.",
+        );
+        if ui.button("Reload schedule") {
+            *meta_events = load_meta_events();
+        }
+    }
+
+    fn render_game_clock_condition_data(from: &mut u32, to: &mut u32, current: u32, ui: &Ui) {
+        ui.header("When in-game clock is between:");
+        ui.time_range_slider("game_clock", from, to, Some(current));
+        ui.text_disabled(format!(
+            "Current in-game time: {}",
+            format_minute_of_day(current)
+        ));
+    }
+
+    fn render_local_time_condition_data(local_time_condition: &mut LocalTimeCondition, ui: &Ui) {
+        ui.header("When local time is between:");
+        let current = local_minute_of_day(Local::now());
+        ui.time_range_slider(
+            "local_time",
+            &mut local_time_condition.from,
+            &mut local_time_condition.to,
+            Some(current),
+        );
+        let weekdays = &mut local_time_condition.weekdays;
+        ui.checkbox("Mon", &mut weekdays.monday);
+        ui.same_line();
+        ui.checkbox("Tue", &mut weekdays.tuesday);
+        ui.same_line();
+        ui.checkbox("Wed", &mut weekdays.wednesday);
+        ui.same_line();
+        ui.checkbox("Thu", &mut weekdays.thursday);
+        ui.same_line();
+        ui.checkbox("Fri", &mut weekdays.friday);
+        ui.same_line();
+        ui.checkbox("Sat", &mut weekdays.saturday);
+        ui.same_line();
+        ui.checkbox("Sun", &mut weekdays.sunday);
+        ui.text_disabled(format!(
+            "Current local time: {}",
+            format_minute_of_day(current)
+        ));
+    }
+
+    fn render_calendar_condition_data(
+        date_range: &mut DateRange,
+        festivals: &mut Vec<Festival>,
+        ui: &Ui,
+    ) {
+        ui.header("When date is between:");
+        if let Some(_c) = ui.begin_combo("Festival##calendar", "Fill from festival") {
+            for festival in festivals.iter() {
+                if Selectable::new(&festival.name)
+                    .selected(festival.from == date_range.from && festival.to == date_range.to)
+                    .build(ui)
+                {
+                    date_range.from = festival.from;
+                    date_range.to = festival.to;
+                }
+            }
+        }
+        Self::render_month_day("From", "calendar_from", &mut date_range.from, ui);
+        Self::render_month_day("To", "calendar_to", &mut date_range.to, ui);
+
+        let today = Local::now().date_naive();
+        let mut recurring = date_range.year.is_none();
+        if ui.checkbox("Repeat every year##calendar", &mut recurring) {
+            date_range.year = if recurring { None } else { Some(today.year()) };
+        }
+        if let Some(year) = &mut date_range.year {
+            Slider::new("Year##calendar", today.year() - 1, today.year() + 5).build(ui, year);
+        }
+        if date_range.contains(today) {
+            ui.text_colored(SUCCESS_COLOR, "Active today");
+        } else {
+            ui.text_disabled("Not active today");
+        }
+        ui.text_disabled("Festival dates can be edited in festivals.json in the addon directory.");
+        if ui.button("Reload festivals") {
+            *festivals = load_festivals();
+        }
+    }
+
+    fn render_month_day(label: &str, id: &str, month_day: &mut MonthDay, ui: &Ui) {
+        ui.text(label);
+        ui.same_line();
+        Slider::new(format!("Month##{}", id), 1, 12).build(ui, &mut month_day.month);
+        // Also fixes impossible dates saved before the day was limited to the month.
+        month_day.clamp_day();
+        ui.same_line();
+        Slider::new(
+            format!("Day##{}", id),
+            1,
+            MonthDay::days_in_month(month_day.month),
+        )
+        .build(ui, &mut month_day.day);
+    }
 }
@@ -1,5 +1,7 @@
 pub mod ui;
 
+use std::path::Path;
+
 pub fn shorten_path(path_str: String) -> String {
     let parts: Vec<&str> = path_str.split(r#"\"#).collect();
     let last_three: Vec<&str> = parts
@@ -13,3 +15,15 @@ pub fn shorten_path(path_str: String) -> String {
         .collect();
     format!("..\\{}", last_three.join("\\"))
 }
+
+pub fn preset_name(preset_path: &Path) -> String {
+    preset_path
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn format_minute_of_day(minute: u32) -> String {
+    format!("{:02}:{:02}", minute / 60 % 24, minute % 60)
+}
@@ -1,3 +1,5 @@
+use crate::context::time_period::day_night_cycle::MINUTES_IN_DAY;
+use crate::render::util::format_minute_of_day;
 use crate::render::util::ui::UiAction;
 use nexus::imgui::{Direction, Slider, SliderFlags, StyleColor, Ui};
 
@@ -12,8 +14,20 @@ pub trait UiExtended {
         last_rule_index: usize,
     );
     fn slider_percent(&self, label: impl AsRef<str>, value: &mut f32) -> bool;
+    fn time_range_slider(
+        &self,
+        label: impl AsRef<str>,
+        from: &mut u32,
+        to: &mut u32,
+        current: Option<u32>,
+    ) -> bool;
 }
 
+const TIMELINE_HEIGHT: f32 = 12.0;
+const TIMELINE_BACKGROUND_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+const TIMELINE_RANGE_COLOR: [f32; 4] = [0.9, 0.6, 0.2, 1.0];
+const TIMELINE_CURRENT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
 impl UiExtended for Ui<'_> {
     fn header<T: AsRef<str>>(&self, text: T) {
         self.text(text);
@@ -83,4 +97,62 @@ impl UiExtended for Ui<'_> {
             false
         }
     }
+
+    fn time_range_slider(
+        &self,
+        label: impl AsRef<str>,
+        from: &mut u32,
+        to: &mut u32,
+        current: Option<u32>,
+    ) -> bool {
+        let [x, y] = self.cursor_screen_pos();
+        let width = self.content_region_avail()[0].min(400.0);
+        let minute_x = |minute: u32| x + width * minute as f32 / MINUTES_IN_DAY as f32;
+        let draw_list = self.get_window_draw_list();
+        draw_list
+            .add_rect(
+                [x, y],
+                [x + width, y + TIMELINE_HEIGHT],
+                TIMELINE_BACKGROUND_COLOR,
+            )
+            .filled(true)
+            .build();
+        let ranges = if *from <= *to {
+            vec![(*from, *to)]
+        } else {
+            vec![(*from, MINUTES_IN_DAY), (0, *to)]
+        };
+        for (range_from, range_to) in ranges {
+            draw_list
+                .add_rect(
+                    [minute_x(range_from), y],
+                    [minute_x(range_to), y + TIMELINE_HEIGHT],
+                    TIMELINE_RANGE_COLOR,
+                )
+                .filled(true)
+                .build();
+        }
+        if let Some(current) = current {
+            draw_list
+                .add_line(
+                    [minute_x(current), y - 2.0],
+                    [minute_x(current), y + TIMELINE_HEIGHT + 2.0],
+                    TIMELINE_CURRENT_COLOR,
+                )
+                .thickness(2.0)
+                .build();
+        }
+        self.dummy([width, TIMELINE_HEIGHT]);
+
+        let label = label.as_ref();
+        let from_changed = Slider::new(format!("From##{}", label), 0, MINUTES_IN_DAY - 1)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .display_format(format_minute_of_day(*from))
+            .build(self, from);
+        let to_changed = Slider::new(format!("To##{}", label), 0, MINUTES_IN_DAY - 1)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .display_format(format_minute_of_day(*to))
+            .build(self, to);
+        from_changed || to_changed
+    }
 }
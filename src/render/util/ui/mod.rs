@@ -15,6 +15,7 @@ pub enum RenderResult {
 pub trait UiElement {
     fn rename(&mut self, _new_name: String) {}
     fn name(&self) -> &String;
+    fn renew_id(&mut self) {}
 }
 
 pub fn process_ui_actions_for_vec<T: UiElement + Clone>(
@@ -32,6 +33,7 @@ pub fn process_ui_actions_for_vec<T: UiElement + Clone>(
                 if let Some(t) = vec.get(i) {
                     let mut new_t = t.clone();
                     new_t.rename(format!("{} (1)", new_t.name()));
+                    new_t.renew_id();
                     vec.insert(0, new_t);
                 }
             }
@@ -3,10 +3,15 @@ mod preset_rule;
 use crate::addon::Addon;
 use crate::config::game_dir;
 use crate::thread::preset_rule::process_preset_rules;
-use crate::util::reshade::{load_reshade_context, switch_to_preset};
-use crate::util::{game_has_focus, is_in_game, is_on_character_select};
+use crate::util::game_has_focus;
+use crate::util::gw2_keybinds::{find_clashes, input_binds_files, load_input_binds};
+use crate::util::reshade::ini_detection::detect_reshade_ini_files;
+use crate::util::reshade::{
+    load_reshade_context, reload_reshade_context_if_changed, switch_to_preset,
+    update_active_preset_from_ipc,
+};
 use function_name::named;
-use log::debug;
+use log::{debug, error};
 use rfd::FileDialog;
 use std::thread;
 use std::time::Duration;
@@ -20,14 +25,26 @@ pub fn background_thread() {
         unsafe { Addon::lock().context.links.update_rtapi() };
         if Addon::lock().config.valid() {
             let reshade_ini_path = Addon::lock().config.reshade.ini_path.clone();
-            load_reshade_context(&reshade_ini_path);
-            if Addon::lock().context.valid() && (game_has_focus() || is_on_character_select()) {
+            reload_reshade_context_if_changed(&reshade_ini_path);
+            update_active_preset_from_ipc();
+            if Addon::lock().context.game_state_changed() {
+                //processed once the game has focus
+                Addon::lock().context.process_manually = true;
+            }
+            let is_in_game = Addon::lock().context.is_in_game();
+            let switch_needs_focus = Addon::lock().config.reshade.switch_backend.needs_focus();
+            Addon::lock().context.update_movement();
+            if Addon::lock().context.valid()
+                && (game_has_focus() || (!is_in_game && !switch_needs_focus))
+            {
                 let mut new_map_id: u32 = 0;
-                if Addon::lock().context.map_changed(&mut new_map_id)
-                    || (is_in_game() && Addon::lock().context.time_period_changed(&mut new_map_id))
-                    || Addon::lock().context.process_manually
-                {
-                    process_preset_rules(new_map_id);
+                let triggered = map_changed(&mut new_map_id)
+                    || (is_in_game && time_period_changed(&new_map_id))
+                    || Addon::lock().context.process_manually;
+                let polled_changed = is_in_game && polled_conditions_changed(&new_map_id);
+                if triggered || polled_changed {
+                    //chance is rolled again only when triggered by map, time period or game state
+                    process_preset_rules(new_map_id, triggered);
                 } else if Addon::lock().context.reshade.should_retry_activation() {
                     let context = Addon::lock().context.reshade.clone();
                     if let Some((preset_path, _)) = context.verify_activation.as_ref() {
@@ -41,6 +58,57 @@ pub fn background_thread() {
     }));
 }
 
+fn map_changed(new_map_id: &mut u32) -> bool {
+    let addon = &mut *Addon::lock();
+    addon
+        .context
+        .map_changed(new_map_id, &addon.config.time_period)
+}
+
+fn time_period_changed(current_map_id: &u32) -> bool {
+    let addon = &mut *Addon::lock();
+    addon
+        .context
+        .time_period_changed(current_map_id, &addon.config.time_period)
+}
+
+fn polled_conditions_changed(current_map_id: &u32) -> bool {
+    let addon = &mut *Addon::lock();
+    addon
+        .context
+        .polled_conditions_changed(&addon.config.preset_rules, current_map_id)
+}
+
+pub fn detect_reshade_ini_files_thread() {
+    Addon::threads().push(thread::spawn(move || {
+        let candidates = detect_reshade_ini_files(&game_dir());
+        Addon::lock().context.ui.reshade_ini_candidates = Some(candidates);
+    }));
+}
+
+pub fn check_keybind_clashes_thread() {
+    Addon::threads().push(thread::spawn(move || {
+        let preset_shortcuts = Addon::lock().context.reshade.preset_shortcuts.clone();
+        let input_binds_files = input_binds_files();
+        let mut clashes = vec![];
+        for input_binds_file in &input_binds_files {
+            match load_input_binds(input_binds_file) {
+                Ok(gw2_keybinds) => clashes.extend(find_clashes(
+                    input_binds_file,
+                    &gw2_keybinds,
+                    &preset_shortcuts,
+                )),
+                Err(e) => error!(
+                    "Could not read GW2 keybinds [{}]: {}",
+                    input_binds_file.display(),
+                    e
+                ),
+            }
+        }
+        Addon::lock().context.ui.keybind_clashes = Some((input_binds_files.len(), clashes));
+    }));
+}
+
 pub fn select_reshade_ini_file_thread() {
     Addon::threads().push(thread::spawn(move || {
         if let Some(file) = FileDialog::new()
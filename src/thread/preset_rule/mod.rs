@@ -1,5 +1,8 @@
 use crate::addon::Addon;
-use crate::util::is_on_character_select;
+use crate::config::preset_rule::PresetRule;
+use crate::context::game_state::GameState;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_to_preset;
 use function_name::named;
 use log::{debug, info};
 use std::sync::MutexGuard;
@@ -7,43 +10,52 @@ use std::sync::MutexGuard;
 const RETRY_COUNT: usize = 10;
 
 #[named]
-pub fn process_preset_rules(new_map_id: u32) {
-    let mut rule_index_to_activate = None;
+pub fn process_preset_rules(new_map_id: u32, reroll_chance: bool) {
     Addon::lock().context.process_manually = false;
-    if !is_on_character_select() {
+    let game_state = Addon::lock().context.game_state.current;
+    if game_state != GameState::InGame {
         debug!(
-            "[{}] Not on character select, processing rules",
-            function_name!()
+            "[{}] Game state is {:?}, not processing rules",
+            function_name!(),
+            game_state
         );
-        let addon = Addon::lock();
+        activate_game_state_preset(Addon::lock(), &game_state);
+        return;
+    }
+
+    let mut rule_index_to_activate = None;
+    debug!("[{}] In game, processing rules", function_name!());
+    let mut addon = Addon::lock();
+    {
+        let addon = &mut *addon;
+        addon
+            .context
+            .condition_state
+            .roll_chances(&addon.config.preset_rules, reroll_chance);
+    }
+    debug!(
+        "[{}] List of rules: {:?}",
+        function_name!(),
+        addon.config.preset_rules
+    );
+    let evaluation_time = addon.context.evaluation_time_now();
+    for (rule_index, preset_rule) in addon.config.preset_rules.iter().enumerate() {
+        debug!("[{}] processing rule {:?}", function_name!(), preset_rule);
+        let result = preset_rule.evaluate(&addon.context, &new_map_id, &evaluation_time);
         debug!(
-            "[{}] List of rules: {:?}",
+            "[{}] rule {:?} evaluated with result {:?}",
             function_name!(),
-            addon.config.preset_rules
+            preset_rule,
+            result
         );
-        for (rule_index, preset_rule) in addon.config.preset_rules.iter().enumerate() {
-            debug!("[{}] processing rule {:?}", function_name!(), preset_rule);
-            let result = preset_rule.evaluate(&addon.context, &new_map_id);
-            debug!(
-                "[{}] rule {:?} evaluated with result {:?}",
-                function_name!(),
-                preset_rule,
-                result
-            );
-            if let Ok(should_activate) = result.activate_rule {
-                if should_activate {
-                    rule_index_to_activate = Some(rule_index);
-                    break;
-                }
+        if let Ok(should_activate) = result.activate_rule {
+            if should_activate {
+                rule_index_to_activate = Some(rule_index);
+                break;
             }
         }
-    } else {
-        debug!(
-            "[{}] On character select, not processing rules",
-            function_name!()
-        );
     }
-    activate_preset_rule(Addon::lock(), rule_index_to_activate);
+    activate_preset_rule(addon, rule_index_to_activate);
 }
 
 #[named]
@@ -65,10 +77,54 @@ pub fn activate_preset_rule(mut addon: MutexGuard<Addon>, rule_index_to_activate
     }
     if let Some(rule) = rule_to_activate {
         let rule = rule.clone();
-        addon.context.reshade.verify_activation = Some((rule.preset_path.clone(), RETRY_COUNT));
+        if rule_preset_active(&rule, &addon.context.reshade) {
+            debug!(
+                "[{}] Preset of rule [{}] is already active",
+                function_name!(),
+                rule.rule_name
+            );
+            return;
+        }
+        addon.context.reshade.transition_duration_ms = rule.transition_duration_ms;
         let reshade_context = &addon.context.reshade.clone();
         //drop to unlock threads
         drop(addon);
-        rule.activate(reshade_context);
+        let preset_path = rule.prepare_activation(reshade_context);
+        Addon::lock().context.reshade.verify_activation = Some((preset_path.clone(), RETRY_COUNT));
+        switch_to_preset(&preset_path, reshade_context);
+    }
+}
+
+// Re-evaluating the rules while the same preset stays selected does not press its keybind again.
+fn rule_preset_active(rule: &PresetRule, reshade_context: &ReshadeContext) -> bool {
+    let preset_path = rule.activation_preset_path(reshade_context);
+    match &reshade_context.verify_activation {
+        Some((pending_preset_path, _)) => *pending_preset_path == preset_path,
+        None => reshade_context.active_preset_path == preset_path,
+    }
+}
+
+#[named]
+fn activate_game_state_preset(mut addon: MutexGuard<Addon>, game_state: &GameState) {
+    if let Some(preset_path) = addon.config.game_state_presets.get(game_state).cloned() {
+        let preset_path = addon.context.reshade.resolve(&preset_path);
+        info!(
+            "[{}] Activating {:?} preset [{}]",
+            function_name!(),
+            game_state,
+            preset_path.display()
+        );
+        addon.context.reshade.verify_activation = Some((preset_path.clone(), RETRY_COUNT));
+        addon.context.reshade.transition_duration_ms = None;
+        let reshade_context = &addon.context.reshade.clone();
+        //drop to unlock threads
+        drop(addon);
+        switch_to_preset(&preset_path, reshade_context);
+    } else {
+        debug!(
+            "[{}] No preset configured for {:?}, keeping current preset",
+            function_name!(),
+            game_state
+        );
     }
 }
@@ -0,0 +1,303 @@
+use crate::context::reshade_context::key_combination::KeyCombination;
+use bimap::BiMap;
+use select::document::Document;
+use select::predicate::Name;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const INPUT_BINDS_DIR: &str = r"Guild Wars 2\InputBinds";
+const SHIFT_MOD: u32 = 1;
+const CTRL_MOD: u32 = 2;
+const ALT_MOD: u32 = 4;
+
+#[derive(Debug, Clone)]
+pub struct Gw2Keybind {
+    pub action: String,
+    pub key_combination: KeyCombination,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeybindClash {
+    pub input_binds_file: String,
+    pub action: String,
+    pub gw2_key_combination: KeyCombination,
+    pub preset_path: PathBuf,
+    pub preset_key_combination: KeyCombination,
+}
+
+impl KeybindClash {
+    // GW2 also triggers a plain key binding when modifiers are held.
+    pub fn is_exact(&self) -> bool {
+        self.gw2_key_combination == self.preset_key_combination
+    }
+}
+
+pub fn input_binds_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ["USERPROFILE", "OneDrive"]
+        .iter()
+        .filter_map(env::var_os)
+        .map(|dir| PathBuf::from(dir).join("Documents").join(INPUT_BINDS_DIR))
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+// Exported as <action name=".." button=".." mod=".." button2=".." mod2=".."/>, device attributes
+// other than keyboard are mouse buttons and skipped.
+pub fn load_input_binds(path: &Path) -> io::Result<Vec<Gw2Keybind>> {
+    let document = Document::from(fs::read_to_string(path)?.as_str());
+    Ok(document
+        .find(Name("action"))
+        .flat_map(|action| {
+            let name = action.attr("name").unwrap_or_default().to_string();
+            ["", "2"]
+                .iter()
+                .filter_map(|suffix| {
+                    let device = action.attr(&format!("device{}", suffix));
+                    if device.is_some_and(|device| !device.eq_ignore_ascii_case("keyboard")) {
+                        return None;
+                    }
+                    let button = action.attr(&format!("button{}", suffix))?.parse().ok()?;
+                    let modifiers = action
+                        .attr(&format!("mod{}", suffix))
+                        .and_then(|modifiers| modifiers.parse().ok())
+                        .unwrap_or(0);
+                    key_combination(button, modifiers)
+                })
+                .map(|key_combination| Gw2Keybind {
+                    action: name.clone(),
+                    key_combination,
+                })
+                .collect::<Vec<Gw2Keybind>>()
+        })
+        .collect())
+}
+
+pub fn find_clashes(
+    input_binds_file: &Path,
+    gw2_keybinds: &[Gw2Keybind],
+    preset_shortcuts: &BiMap<KeyCombination, PathBuf>,
+) -> Vec<KeybindClash> {
+    let file_name = input_binds_file
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut clashes = vec![];
+    for (preset_key_combination, preset_path) in preset_shortcuts {
+        let plain_key = KeyCombination {
+            key_code: preset_key_combination.key_code.clone(),
+            ..Default::default()
+        };
+        for keybind in gw2_keybinds.iter().filter(|keybind| {
+            keybind.key_combination == *preset_key_combination
+                || keybind.key_combination == plain_key
+        }) {
+            clashes.push(KeybindClash {
+                input_binds_file: file_name.clone(),
+                action: keybind.action.clone(),
+                gw2_key_combination: keybind.key_combination.clone(),
+                preset_path: preset_path.clone(),
+                preset_key_combination: preset_key_combination.clone(),
+            });
+        }
+    }
+    clashes
+}
+
+fn key_combination(button: u32, modifiers: u32) -> Option<KeyCombination> {
+    Some(KeyCombination {
+        key_code: gw2_key_to_virtual_key(button)?.to_string(),
+        ctrl: modifiers & CTRL_MOD != 0,
+        shift: modifiers & SHIFT_MOD != 0,
+        alt: modifiers & ALT_MOD != 0,
+    })
+}
+
+// GW2 uses its own key codes. Digits and letters match virtual-key codes, unknown codes are skipped.
+fn gw2_key_to_virtual_key(button: u32) -> Option<u32> {
+    let virtual_key = match button {
+        3 => 0xDE,
+        4 => 0xDC,
+        5 => 0x14,
+        7 => 0xBD,
+        8 => 0xBB,
+        9 => 0x1B,
+        10 => 0xDB,
+        11 => 0x90,
+        12 => 0xBE,
+        13 => 0xDD,
+        14 => 0xBA,
+        15 => 0xBF,
+        16 => 0x2C,
+        17 => 0xC0,
+        18 => 0x08,
+        19 => 0x2E,
+        20 => 0x0D,
+        21 => 0x20,
+        22 => 0x09,
+        23 => 0x23,
+        24 => 0x24,
+        25 => 0x2D,
+        26 => 0x22,
+        27 => 0x21,
+        28 => 0x28,
+        29 => 0x25,
+        30 => 0x27,
+        31 => 0x26,
+        32..=43 => button - 32 + 0x70,
+        48..=57 | 65..=90 => button,
+        _ => return None,
+    };
+    Some(virtual_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key_code: u32, ctrl: bool, shift: bool, alt: bool) -> KeyCombination {
+        KeyCombination {
+            key_code: key_code.to_string(),
+            ctrl,
+            shift,
+            alt,
+        }
+    }
+
+    fn fixture_keybinds() -> Vec<Gw2Keybind> {
+        load_input_binds(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join("gw2_input_binds.xml"),
+        )
+        .unwrap()
+    }
+
+    fn keybinds_of<'a>(keybinds: &'a [Gw2Keybind], action: &str) -> Vec<&'a KeyCombination> {
+        keybinds
+            .iter()
+            .filter(|keybind| keybind.action == action)
+            .map(|keybind| &keybind.key_combination)
+            .collect()
+    }
+
+    #[test]
+    fn loads_keyboard_binds_from_export() {
+        let keybinds = fixture_keybinds();
+        let plain = |key_code| key(key_code, false, false, false);
+        assert_eq!(
+            keybinds_of(&keybinds, "Move Forward"),
+            [&plain(0x57), &plain(0x26)]
+        );
+        assert_eq!(keybinds_of(&keybinds, "Jump"), [&plain(0x20)]);
+        assert_eq!(keybinds_of(&keybinds, "Healing Skill"), [&plain(0x36)]);
+        assert_eq!(keybinds_of(&keybinds, "Screenshot"), [&plain(0x2C)]);
+        assert_eq!(keybinds_of(&keybinds, "Toggle Chat"), [&plain(0x0D)]);
+        assert!(keybinds_of(&keybinds, "About Face").is_empty());
+        assert!(keybinds_of(&keybinds, "Unbound Action").is_empty());
+    }
+
+    #[test]
+    fn loads_modifier_bits() {
+        let keybinds = fixture_keybinds();
+        assert_eq!(
+            keybinds_of(&keybinds, "Special Action Skill"),
+            [&key(0x70, false, true, false)]
+        );
+        assert_eq!(
+            keybinds_of(&keybinds, "Stow/Draw Weapons"),
+            [&key(0x7B, true, false, false)]
+        );
+        assert_eq!(
+            keybinds_of(&keybinds, "Squad Marker 1"),
+            [&key(0x31, false, false, true)]
+        );
+        assert_eq!(
+            keybinds_of(&keybinds, "Squad Marker 2"),
+            [&key(0x32, true, true, false)]
+        );
+        assert_eq!(
+            keybinds_of(&keybinds, "Mount/Dismount"),
+            [&key(0x58, true, true, true)]
+        );
+    }
+
+    #[test]
+    fn gw2_keys_map_to_virtual_keys() {
+        let cases = [
+            (3, 0xDE),
+            (9, 0x1B),
+            (17, 0xC0),
+            (18, 0x08),
+            (21, 0x20),
+            (28, 0x28),
+            (29, 0x25),
+            (30, 0x27),
+            (31, 0x26),
+            (32, 0x70),
+            (43, 0x7B),
+            (48, 0x30),
+            (57, 0x39),
+            (65, 0x41),
+            (90, 0x5A),
+        ];
+        for (button, virtual_key) in cases {
+            assert_eq!(
+                gw2_key_to_virtual_key(button),
+                Some(virtual_key),
+                "{}",
+                button
+            );
+        }
+        for button in [0, 1, 2, 6, 44, 47, 58, 64, 91, 999] {
+            assert_eq!(gw2_key_to_virtual_key(button), None, "{}", button);
+        }
+    }
+
+    #[test]
+    fn reports_every_clash() {
+        let keybinds = fixture_keybinds();
+        let mut preset_shortcuts = BiMap::new();
+        preset_shortcuts.insert(key(0x70, false, true, false), PathBuf::from("Day.ini"));
+        preset_shortcuts.insert(key(0x31, true, false, false), PathBuf::from("Night.ini"));
+        preset_shortcuts.insert(key(0x46, false, false, false), PathBuf::from("Dawn.ini"));
+        preset_shortcuts.insert(key(0x7C, false, false, false), PathBuf::from("Dusk.ini"));
+        let mut clashes: Vec<(String, String, bool)> = find_clashes(
+            Path::new("gw2_input_binds.xml"),
+            &keybinds,
+            &preset_shortcuts,
+        )
+        .into_iter()
+        .map(|clash| {
+            assert_eq!(clash.input_binds_file, "gw2_input_binds.xml");
+            (
+                clash.preset_path.to_string_lossy().to_string(),
+                clash.action.clone(),
+                clash.is_exact(),
+            )
+        })
+        .collect();
+        clashes.sort();
+        let clash =
+            |preset: &str, action: &str, exact| (preset.to_string(), action.to_string(), exact);
+        assert_eq!(
+            clashes,
+            [
+                clash("Dawn.ini", "Interact", true),
+                clash("Dawn.ini", "Take All", true),
+                clash("Day.ini", "Profession Skill 1", false),
+                clash("Day.ini", "Special Action Skill", true),
+                clash("Night.ini", "Weapon Skill 1", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Line-based model of an INI file. Every line keeps its original text, so
+// unchanged files are written back byte for byte.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ini {
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    // Keys before the first header belong to the unnamed global section.
+    pub name: String,
+    header: Option<String>,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Entry {
+        key: String,
+        value: String,
+        raw: String,
+    },
+    Other(String),
+}
+
+impl Ini {
+    pub fn parse(content: &str) -> Ini {
+        let mut sections = vec![Section::new(String::new(), None)];
+        for raw in content.split_inclusive('\n') {
+            let text = raw.trim_end_matches(['\r', '\n']).trim();
+            if let Some(name) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                sections.push(Section::new(name.trim().to_string(), Some(raw.to_string())));
+                continue;
+            }
+            let line = match text.split_once('=') {
+                Some((key, value)) if !is_comment(text) => Line::Entry {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                    raw: raw.to_string(),
+                },
+                _ => Line::Other(raw.to_string()),
+            };
+            sections.last_mut().unwrap().lines.push(line);
+        }
+        Ini { sections }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Ini> {
+        fs::read_to_string(path).map(|content| Ini::parse(&content))
+    }
+
+    // Written to a temporary file first, so the original is never left half written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, self.to_string())?;
+        fs::rename(&temp_path, path)
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter()
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?.get(key)
+    }
+
+    // Comma separated values as used by ReShade for lists.
+    pub fn get_list(&self, section: &str, key: &str) -> Vec<String> {
+        self.get(section, key)
+            .map(|value| {
+                value
+                    .split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Updates the key in place, or appends it to the section, creating the section if needed.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let line_ending = self.line_ending();
+        let index = match self
+            .sections
+            .iter()
+            .position(|s| s.name.eq_ignore_ascii_case(section))
+        {
+            Some(index) => index,
+            None => {
+                self.ensure_trailing_line_ending(line_ending);
+                let header = (!section.is_empty()).then(|| format!("[{}]{}", section, line_ending));
+                self.sections
+                    .push(Section::new(section.to_string(), header));
+                self.sections.len() - 1
+            }
+        };
+        self.sections[index].set(key, value, line_ending);
+    }
+
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        self.sections
+            .iter_mut()
+            .find(|s| s.name.eq_ignore_ascii_case(section))
+            .is_some_and(|section| section.remove(key))
+    }
+
+    fn line_ending(&self) -> &'static str {
+        let crlf = self
+            .raw_lines()
+            .next()
+            .is_some_and(|raw| raw.ends_with("\r\n"));
+        if crlf {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    fn raw_lines(&self) -> impl Iterator<Item = &String> {
+        self.sections.iter().flat_map(|section| section.raw_lines())
+    }
+
+    fn ensure_trailing_line_ending(&mut self, line_ending: &str) {
+        if let Some(last) = self
+            .sections
+            .iter_mut()
+            .rev()
+            .find_map(|section| section.last_raw_mut())
+        {
+            if !last.ends_with('\n') {
+                last.push_str(line_ending);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ini {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for raw in self.raw_lines() {
+            write!(f, "{}", raw)?;
+        }
+        Ok(())
+    }
+}
+
+impl Section {
+    fn new(name: String, header: Option<String>) -> Section {
+        Section {
+            name,
+            header,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .find(|(entry_key, _)| entry_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry { key, value, .. } => Some((key.as_str(), value.as_str())),
+            Line::Other(_) => None,
+        })
+    }
+
+    fn set(&mut self, key: &str, value: &str, line_ending: &str) {
+        let existing = self.lines.iter_mut().find_map(|line| match line {
+            Line::Entry {
+                key: entry_key,
+                value: entry_value,
+                raw,
+            } if entry_key.eq_ignore_ascii_case(key) => Some((entry_key, entry_value, raw)),
+            _ => None,
+        });
+        if let Some((entry_key, entry_value, raw)) = existing {
+            if entry_value != value {
+                let ending = &raw[raw.trim_end_matches(['\r', '\n']).len()..];
+                *raw = format!("{}={}{}", entry_key, value, ending);
+                *entry_value = value.to_string();
+            }
+            return;
+        }
+        let entry = Line::Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            raw: format!("{}={}{}", key, value, line_ending),
+        };
+        // Keep blank lines separating sections after the new key.
+        let insert_index = self
+            .lines
+            .iter()
+            .rposition(|line| !matches!(line, Line::Other(raw) if raw.trim().is_empty()))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        if let Some(Line::Entry { raw, .. } | Line::Other(raw)) = insert_index
+            .checked_sub(1)
+            .and_then(|i| self.lines.get_mut(i))
+        {
+            if !raw.ends_with('\n') {
+                raw.push_str(line_ending);
+            }
+        }
+        self.lines.insert(insert_index, entry);
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        let len = self.lines.len();
+        self.lines.retain(
+            |line| !matches!(line, Line::Entry { key: entry_key, .. } if entry_key.eq_ignore_ascii_case(key)),
+        );
+        self.lines.len() != len
+    }
+
+    fn raw_lines(&self) -> impl Iterator<Item = &String> {
+        self.header
+            .iter()
+            .chain(self.lines.iter().map(|line| match line {
+                Line::Entry { raw, .. } | Line::Other(raw) => raw,
+            }))
+    }
+
+    fn last_raw_mut(&mut self) -> Option<&mut String> {
+        match self.lines.last_mut() {
+            Some(Line::Entry { raw, .. } | Line::Other(raw)) => Some(raw),
+            None => self.header.as_mut(),
+        }
+    }
+}
+
+fn is_comment(text: &str) -> bool {
+    text.starts_with(';') || text.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures() -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let mut fixtures: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ini"))
+            .collect();
+        fixtures.sort();
+        fixtures
+    }
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn fixtures_round_trip_byte_exact() {
+        let fixtures = fixtures();
+        assert!(fixtures.len() >= 4);
+        for path in fixtures {
+            let content = fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                Ini::read(&path).unwrap().to_string(),
+                content,
+                "{}",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn reads_reshade_ini() {
+        let ini = Ini::parse(&fixture("reshade5_ReShade.ini"));
+        assert_eq!(
+            ini.get("general", "PresetPath"),
+            Some(r".\reshade-presets\Day.ini")
+        );
+        assert_eq!(ini.get_list("GENERAL", "PresetShortcutKeys").len(), 12);
+        assert_eq!(ini.get("INPUT", "KeyReload"), Some("116,1,0,0"));
+        let ini = Ini::parse(&fixture("reshade6_dxgi.ini"));
+        assert!(ini.get_list("GENERAL", "PresetShortcutPaths").is_empty());
+        assert_eq!(ini.get_list("GENERAL", "EffectSearchPaths").len(), 3);
+        assert_eq!(ini.get("STYLE", "HdrOverlayBrightness"), Some("203.000000"));
+        let preset = Ini::parse(&fixture("reshade6_preset.ini"));
+        assert_eq!(preset.get_list("", "Techniques").len(), 8);
+        assert_eq!(preset.get_list("", "TechniqueSorting").len(), 15);
+        assert_eq!(
+            preset.get("qUINT_mxao.fx", "MXAO_SAMPLE_RADIUS"),
+            Some("2.500000")
+        );
+        let preset = Ini::parse(&fixture("edited_preset_no_trailing_newline.ini"));
+        assert_eq!(preset.get("Vibrance.fx", "Vibrance"), Some("0.300000"));
+        assert_eq!(preset.get("", "Techniques"), Some("Vibrance@Vibrance.fx"));
+    }
+
+    #[test]
+    fn set_existing_key_changes_only_that_line() {
+        let content = fixture("reshade5_ReShade.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("GENERAL", "PresetPath", r".\reshade-presets\Night.ini");
+        let expected = content.replace(
+            "PresetPath=.\\reshade-presets\\Day.ini\r\n",
+            "PresetPath=.\\reshade-presets\\Night.ini\r\n",
+        );
+        assert_eq!(ini.to_string(), expected);
+    }
+
+    #[test]
+    fn set_same_value_keeps_spacing() {
+        let content = fixture("edited_preset_no_trailing_newline.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("Vibrance.fx", "Vibrance", "0.300000");
+        assert_eq!(ini.to_string(), content);
+    }
+
+    #[test]
+    fn set_new_key_keeps_comments_order_and_blank_lines() {
+        let content = fixture("edited_preset_no_trailing_newline.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("Vibrance.fx", "VibranceStrength", "1.0");
+        let expected = content.replace(
+            "VibranceRGBBalance=1.000000,1.000000,1.000000\n\n\n",
+            "VibranceRGBBalance=1.000000,1.000000,1.000000\nVibranceStrength=1.0\n\n\n",
+        );
+        assert_eq!(ini.to_string(), expected);
+    }
+
+    #[test]
+    fn set_in_last_section_without_trailing_newline() {
+        let content = fixture("edited_preset_no_trailing_newline.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("Tonemap.fx", "Gamma", "1.0");
+        assert_eq!(ini.to_string(), format!("{}\nGamma=1.0\n", content));
+    }
+
+    #[test]
+    fn set_new_section_uses_crlf() {
+        let content = fixture("reshade5_preset.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("Bloom.fx", "BloomIntensity", "1.2");
+        assert_eq!(
+            ini.to_string(),
+            format!("{}[Bloom.fx]\r\nBloomIntensity=1.2\r\n", content)
+        );
+    }
+
+    #[test]
+    fn set_global_key_before_first_section() {
+        let content = fixture("reshade5_preset.ini");
+        let mut ini = Ini::parse(&content);
+        ini.set("", "Key", "1");
+        let expected = content.replace(
+            "TechniqueSorting=Tonemap@Tonemap.fx,Vibrance@Vibrance.fx,LumaSharpen@LumaSharpen.fx,DOF@DOF.fx\r\n",
+            "TechniqueSorting=Tonemap@Tonemap.fx,Vibrance@Vibrance.fx,LumaSharpen@LumaSharpen.fx,DOF@DOF.fx\r\nKey=1\r\n",
+        );
+        assert_eq!(ini.to_string(), expected);
+    }
+
+    #[test]
+    fn remove_keeps_other_lines() {
+        let content = fixture("reshade5_ReShade.ini");
+        let mut ini = Ini::parse(&content);
+        assert!(ini.remove("general", "presettransitionduration"));
+        assert!(!ini.remove("GENERAL", "PresetTransitionDuration"));
+        assert_eq!(
+            ini.to_string(),
+            content.replace("PresetTransitionDuration=1000\r\n", "")
+        );
+    }
+
+    #[test]
+    fn comments_are_not_entries() {
+        let ini = Ini::parse("; Key=1\n# Other=2\n[A]\n;Key=3\n");
+        assert_eq!(ini.get("", "; Key"), None);
+        assert_eq!(ini.section("A").unwrap().entries().count(), 0);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod gw2_keybinds;
+pub mod ini;
 pub mod reshade;
+#[cfg(test)]
+pub mod test_dir;
 
 use crate::addon::Addon;
 use function_name::named;
@@ -13,20 +17,6 @@ pub fn game_has_focus() -> bool {
     false
 }
 
-pub fn is_in_game() -> bool {
-    let mut is_gameplay = false;
-    if let Some(nexus) = unsafe { Addon::lock().context.links.nexus() } {
-        if nexus.is_gameplay {
-            is_gameplay = true;
-        }
-    }
-    is_gameplay
-}
-
-pub fn is_on_character_select() -> bool {
-    !is_in_game()
-}
-
 pub fn true_if_1() -> fn(&String) -> bool {
     |value| value == "1"
 }
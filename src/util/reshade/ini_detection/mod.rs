@@ -0,0 +1,88 @@
+use crate::util::ini::Ini;
+use crate::util::reshade::preset_discovery::INPUT_SECTION;
+use crate::util::reshade::preset_path::resolve_preset_path;
+use crate::util::reshade::{GENERAL_SECTION, PRESET_SHORTCUT_PATHS};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SUBFOLDERS: [&str; 3] = ["", "bin64", "reshade"];
+const GENERAL_KEYS: [&str; 3] = ["EffectSearchPaths", "PresetPath", "TextureSearchPaths"];
+
+#[derive(Debug, Clone)]
+pub struct ReshadeIniCandidate {
+    pub path: PathBuf,
+    pub preset_keybinds: usize,
+    pub missing_presets: usize,
+    pub active_preset: Option<String>,
+}
+
+impl ReshadeIniCandidate {
+    pub fn valid(&self) -> bool {
+        self.preset_keybinds > 0 && self.missing_presets == 0
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = match self.preset_keybinds {
+            0 => "No preset keybinds".to_string(),
+            1 => "1 preset keybind".to_string(),
+            count => format!("{} preset keybinds", count),
+        };
+        if self.missing_presets > 0 {
+            summary.push_str(&format!(", {} missing", self.missing_presets));
+        }
+        if let Some(active_preset) = &self.active_preset {
+            summary.push_str(&format!(", active: {}", active_preset));
+        }
+        summary
+    }
+}
+
+pub fn detect_reshade_ini_files(game_dir: &Path) -> Vec<ReshadeIniCandidate> {
+    SUBFOLDERS
+        .iter()
+        .map(|subfolder| game_dir.join(subfolder))
+        .filter_map(|folder| fs::read_dir(folder).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("ini"))
+        })
+        .filter_map(|path| {
+            let ini = Ini::read(&path).ok()?;
+            is_reshade_ini(&ini).then(|| candidate(path, &ini, game_dir))
+        })
+        .collect()
+}
+
+// ReShade names its configuration after the dll it was installed as, e.g. dxgi.ini or d3d11.ini,
+// so files are recognized by their content instead.
+fn is_reshade_ini(ini: &Ini) -> bool {
+    GENERAL_KEYS
+        .iter()
+        .any(|key| ini.get(GENERAL_SECTION, key).is_some())
+        || ini.get(INPUT_SECTION, "KeyOverlay").is_some()
+}
+
+fn candidate(path: PathBuf, ini: &Ini, game_dir: &Path) -> ReshadeIniCandidate {
+    let base_paths: Vec<PathBuf> = path
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain([game_dir.to_path_buf()])
+        .collect();
+    let preset_paths = ini.get_list(GENERAL_SECTION, PRESET_SHORTCUT_PATHS);
+    let missing_presets = preset_paths
+        .iter()
+        .filter(|preset_path| !resolve_preset_path(Path::new(preset_path), &base_paths).exists())
+        .count();
+    ReshadeIniCandidate {
+        preset_keybinds: preset_paths.len(),
+        missing_presets,
+        active_preset: ini
+            .get(GENERAL_SECTION, "PresetPath")
+            .and_then(|preset_path| Path::new(preset_path).file_stem())
+            .map(|file_stem| file_stem.to_string_lossy().to_string()),
+        path,
+    }
+}
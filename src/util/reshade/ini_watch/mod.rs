@@ -0,0 +1,87 @@
+use chrono::{DateTime, Local};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// ReShade writes the file in several steps, so it is only read once metadata stops changing.
+const DEBOUNCE: Duration = Duration::from_millis(400);
+const PRESET_FILES_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default)]
+pub struct ReshadeIniWatch {
+    path: PathBuf,
+    metadata: Option<(Option<SystemTime>, u64)>,
+    metadata_changed_at: Option<Instant>,
+    content_hash: Option<u64>,
+    preset_files_checked_at: Option<Instant>,
+    pub last_change: Option<DateTime<Local>>,
+}
+
+impl ReshadeIniWatch {
+    // Returns file content when it has to be parsed again.
+    pub fn poll(&mut self, path: &Path) -> Option<String> {
+        if path != self.path {
+            let content = fs::read_to_string(path).ok()?;
+            self.loaded(path, &content);
+            return Some(content);
+        }
+        let metadata = file_metadata(path);
+        if metadata != self.metadata {
+            self.metadata = metadata;
+            self.metadata_changed_at = Some(Instant::now());
+            return None;
+        }
+        if self.metadata_changed_at?.elapsed() < DEBOUNCE {
+            return None;
+        }
+        self.metadata_changed_at = None;
+        let content = fs::read_to_string(path).ok()?;
+        if self.content_hash == Some(content_hash(&content)) {
+            return None;
+        }
+        self.loaded(path, &content);
+        Some(content)
+    }
+
+    pub fn loaded(&mut self, path: &Path, content: &str) {
+        self.path = path.to_path_buf();
+        self.metadata = file_metadata(path);
+        self.metadata_changed_at = None;
+        self.content_hash = Some(content_hash(content));
+        self.preset_files_checked_at = Some(Instant::now());
+        self.last_change = self
+            .metadata
+            .and_then(|(modified, _)| modified)
+            .map(DateTime::<Local>::from);
+    }
+
+    // ReShade saved the file, but it was not read yet.
+    pub fn change_pending(&self) -> bool {
+        self.metadata_changed_at.is_some()
+    }
+
+    pub fn preset_files_check_due(&mut self) -> bool {
+        if self
+            .preset_files_checked_at
+            .is_some_and(|checked_at| checked_at.elapsed() < PRESET_FILES_CHECK_INTERVAL)
+        {
+            return false;
+        }
+        self.preset_files_checked_at = Some(Instant::now());
+        true
+    }
+}
+
+fn file_metadata(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
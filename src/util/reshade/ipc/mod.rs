@@ -0,0 +1,547 @@
+use function_name::named;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// Messages are JSON objects, one per line. The client starts with Hello and
+// the server answers with its own protocol version before any other request.
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const PIPE_NAME: &str = r"\\.\pipe\reshade_preset_switcher";
+#[cfg(windows)]
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Hello { version: u32 },
+    SwitchPreset { path: PathBuf },
+    GetCurrentPreset,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Hello { version: u32 },
+    Ok,
+    CurrentPreset { path: PathBuf },
+    Error { message: String },
+    // Sent by the server whenever the active preset changes, may arrive between responses.
+    PresetChanged { path: PathBuf },
+}
+
+// Reading side of the pipe. Only bytes already available are read, so the reader
+// thread never blocks and can be stopped before the addon is unloaded.
+pub trait PipeRead: Read + Send + 'static {
+    // Number of bytes that can be read without blocking, an error once the pipe is closed.
+    fn available(&mut self) -> io::Result<usize>;
+}
+
+#[cfg(windows)]
+impl PipeRead for std::fs::File {
+    fn available(&mut self) -> io::Result<usize> {
+        use std::os::windows::io::AsRawHandle;
+        let mut available = 0;
+        let result = unsafe {
+            PeekNamedPipe(
+                self.as_raw_handle(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                &mut available,
+                std::ptr::null_mut(),
+            )
+        };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(available as usize)
+    }
+}
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+extern "system" {
+    fn PeekNamedPipe(
+        pipe: std::os::windows::io::RawHandle,
+        buffer: *mut std::ffi::c_void,
+        buffer_size: u32,
+        bytes_read: *mut u32,
+        total_bytes_available: *mut u32,
+        bytes_left_this_message: *mut u32,
+    ) -> i32;
+}
+
+pub struct IpcClient {
+    writer: Box<dyn Write + Send>,
+    messages: Receiver<io::Result<Message>>,
+    notifications: Vec<PathBuf>,
+    timeout: Duration,
+    stop: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl IpcClient {
+    pub fn connect() -> io::Result<IpcClient> {
+        Self::connect_to(Path::new(PIPE_NAME))
+    }
+
+    #[cfg(windows)]
+    pub fn connect_to(pipe_path: &Path) -> io::Result<IpcClient> {
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(pipe_path)?;
+        IpcClient::new(pipe.try_clone()?, pipe, RESPONSE_TIMEOUT)
+    }
+
+    #[cfg(not(windows))]
+    pub fn connect_to(_pipe_path: &Path) -> io::Result<IpcClient> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn new(
+        reader: impl PipeRead,
+        writer: impl Write + Send + 'static,
+        timeout: Duration,
+    ) -> io::Result<IpcClient> {
+        let (sender, messages) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let stop = stop.clone();
+            thread::spawn(move || read_messages(reader, sender, &stop))
+        };
+        let mut client = IpcClient {
+            writer: Box::new(writer),
+            messages,
+            notifications: Vec::new(),
+            timeout,
+            stop,
+            reader: Some(reader),
+        };
+        match client.request(&Request::Hello {
+            version: PROTOCOL_VERSION,
+        })? {
+            Message::Hello { version } if version == PROTOCOL_VERSION => Ok(client),
+            Message::Hello { version } => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "protocol version {} is not supported, expected {}",
+                    version, PROTOCOL_VERSION
+                ),
+            )),
+            message => Err(unexpected(message)),
+        }
+    }
+
+    pub fn switch_preset(&mut self, preset_path: &Path) -> io::Result<()> {
+        match self.request(&Request::SwitchPreset {
+            path: preset_path.to_path_buf(),
+        })? {
+            Message::Ok => Ok(()),
+            message => Err(unexpected(message)),
+        }
+    }
+
+    pub fn current_preset(&mut self) -> io::Result<PathBuf> {
+        match self.request(&Request::GetCurrentPreset)? {
+            Message::CurrentPreset { path } => Ok(path),
+            message => Err(unexpected(message)),
+        }
+    }
+
+    // Presets reported by PresetChanged notifications since the last call, oldest first.
+    // Notifications are read without waiting, an error means the connection is gone.
+    pub fn take_notifications(&mut self) -> io::Result<Vec<PathBuf>> {
+        loop {
+            match self.messages.try_recv() {
+                Ok(Ok(Message::PresetChanged { path })) => self.notifications.push(path),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) if e.kind() == io::ErrorKind::InvalidData => {}
+                Ok(Err(e)) => return Err(e),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            }
+        }
+        Ok(std::mem::take(&mut self.notifications))
+    }
+
+    fn request(&mut self, request: &Request) -> io::Result<Message> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+        loop {
+            match self.read_message()? {
+                Message::PresetChanged { path } => self.notifications.push(path),
+                Message::Error { message } => return Err(io::Error::other(message)),
+                message => return Ok(message),
+            }
+        }
+    }
+
+    fn read_message(&mut self) -> io::Result<Message> {
+        match self.messages.recv_timeout(self.timeout) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "companion add-on did not respond",
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+// Joining the reader keeps it from running addon code after the addon is unloaded.
+impl Drop for IpcClient {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+fn read_messages(
+    mut reader: impl PipeRead,
+    sender: Sender<io::Result<Message>>,
+    stop: &AtomicBool,
+) {
+    let mut pending: Vec<u8> = Vec::new();
+    while !stop.load(Ordering::Relaxed) {
+        let available = match reader.available() {
+            Ok(0) => {
+                thread::sleep(READ_POLL_INTERVAL);
+                continue;
+            }
+            Ok(available) => available,
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        let mut buffer = vec![0; available];
+        match reader.read(&mut buffer) {
+            Ok(0) => {
+                let _ = sender.send(Err(io::ErrorKind::UnexpectedEof.into()));
+                return;
+            }
+            Ok(read) => pending.extend_from_slice(&buffer[..read]),
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        }
+        while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let message = serde_json::from_slice(&line).map_err(io::Error::from);
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// Connection kept between switches, clones of the context share it.
+// It is dropped on errors and opened again on the next switch.
+#[derive(Clone, Default)]
+pub struct IpcConnection {
+    client: Arc<Mutex<Option<IpcClient>>>,
+}
+
+impl IpcConnection {
+    #[named]
+    pub fn connect(&self) -> bool {
+        let mut client = self.client.lock().unwrap();
+        if client.is_none() {
+            match IpcClient::connect() {
+                Ok(connected) => *client = Some(connected),
+                Err(e) => debug!(
+                    "[{}] Companion add-on not available: {}",
+                    function_name!(),
+                    e
+                ),
+            }
+        }
+        client.is_some()
+    }
+
+    pub fn with_client<T>(
+        &self,
+        request: impl FnOnce(&mut IpcClient) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut client = self.client.lock().unwrap();
+        let connected = client
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "not connected"))?;
+        let result = request(connected);
+        if result
+            .as_ref()
+            .is_err_and(|e| e.kind() != io::ErrorKind::Other)
+        {
+            *client = None;
+        }
+        result
+    }
+
+    // Only an open connection is polled, it is not opened for this.
+    pub fn take_notifications(&self) -> Vec<PathBuf> {
+        if self.client.lock().unwrap().is_none() {
+            return Vec::new();
+        }
+        self.with_client(IpcClient::take_notifications)
+            .unwrap_or_default()
+    }
+
+    pub fn disconnect(&self) {
+        *self.client.lock().unwrap() = None;
+    }
+}
+
+impl fmt::Debug for IpcConnection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let connected = self
+            .client
+            .try_lock()
+            .map(|client| client.is_some().to_string())
+            .unwrap_or_else(|_| "busy".to_string());
+        write!(f, "IpcConnection {{ connected: {} }}", connected)
+    }
+}
+
+fn unexpected(message: Message) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected message {:?}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Instant;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    // Stand-in for the companion add-on, reading requests and writing raw lines.
+    struct Server {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Server {
+        fn expect(&mut self, request: Request) {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            assert_eq!(serde_json::from_str::<Request>(&line).unwrap(), request);
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{}", line).unwrap();
+        }
+
+        fn hello(&mut self) {
+            self.expect(Request::Hello {
+                version: PROTOCOL_VERSION,
+            });
+            self.send(r#"{"type":"hello","version":1}"#);
+        }
+    }
+
+    // Client side of the socket, reports when the reader thread has dropped it.
+    struct TestPipe {
+        stream: TcpStream,
+        _dropped: Sender<()>,
+    }
+
+    impl Read for TestPipe {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.stream.read(buffer)
+        }
+    }
+
+    impl PipeRead for TestPipe {
+        fn available(&mut self) -> io::Result<usize> {
+            let mut buffer = [0; 1024];
+            match self.stream.peek(&mut buffer) {
+                Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(available) => Ok(available),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(0),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    fn connect(
+        serve: impl FnOnce(&mut Server) + Send + 'static,
+    ) -> (io::Result<IpcClient>, JoinHandle<()>) {
+        let (client, server, _) = connect_with_drop_signal(serve);
+        (client, server)
+    }
+
+    fn connect_with_drop_signal(
+        serve: impl FnOnce(&mut Server) + Send + 'static,
+    ) -> (io::Result<IpcClient>, JoinHandle<()>, Receiver<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut server = Server {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+            };
+            serve(&mut server);
+        });
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let (dropped, reader_dropped) = channel();
+        let pipe = TestPipe {
+            stream: stream.try_clone().unwrap(),
+            _dropped: dropped,
+        };
+        let client = IpcClient::new(pipe, stream, TIMEOUT);
+        (client, server, reader_dropped)
+    }
+
+    #[test]
+    fn hello_with_matching_version() {
+        let (client, server) = connect(|server| server.hello());
+        assert!(client.is_ok());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn hello_with_other_version() {
+        let (client, server) = connect(|server| {
+            server.expect(Request::Hello {
+                version: PROTOCOL_VERSION,
+            });
+            server.send(r#"{"type":"hello","version":2}"#);
+        });
+        assert_eq!(
+            client.err().map(|e| e.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn switch_preset_ok_and_error() {
+        let (client, server) = connect(|server| {
+            server.hello();
+            let request = Request::SwitchPreset {
+                path: PathBuf::from(r"C:\presets\Day.ini"),
+            };
+            server.expect(request.clone());
+            server.send(r#"{"type":"ok"}"#);
+            server.expect(request);
+            server.send(r#"{"type":"error","message":"preset not found"}"#);
+        });
+        let mut client = client.unwrap();
+        let preset_path = Path::new(r"C:\presets\Day.ini");
+        assert!(client.switch_preset(preset_path).is_ok());
+        let error = client.switch_preset(preset_path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        assert_eq!(error.to_string(), "preset not found");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn get_current_preset() {
+        let (client, server) = connect(|server| {
+            server.hello();
+            server.expect(Request::GetCurrentPreset);
+            server.send(r#"{"type":"current_preset","path":"C:\\presets\\Night.ini"}"#);
+        });
+        assert_eq!(
+            client.unwrap().current_preset().unwrap(),
+            PathBuf::from(r"C:\presets\Night.ini")
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn preset_changed_between_responses() {
+        let (done, open) = channel::<()>();
+        let (client, server) = connect(move |server| {
+            server.hello();
+            server.expect(Request::GetCurrentPreset);
+            server.send(r#"{"type":"preset_changed","path":"Dusk.ini"}"#);
+            server.send(r#"{"type":"current_preset","path":"Dusk.ini"}"#);
+            server.send(r#"{"type":"preset_changed","path":"Night.ini"}"#);
+            let _ = open.recv();
+        });
+        let mut client = client.unwrap();
+        assert_eq!(client.current_preset().unwrap(), PathBuf::from("Dusk.ini"));
+
+        let mut notifications = vec![];
+        let start = Instant::now();
+        while notifications.len() < 2 && start.elapsed() < TIMEOUT {
+            notifications.extend(client.take_notifications().unwrap());
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            notifications,
+            [PathBuf::from("Dusk.ini"), PathBuf::from("Night.ini")]
+        );
+        assert!(client.take_notifications().unwrap().is_empty());
+        done.send(()).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn connection_closed() {
+        let (client, server) = connect(|server| server.hello());
+        let mut client = client.unwrap();
+        server.join().unwrap();
+        assert!(client.current_preset().is_err());
+        let start = Instant::now();
+        while client.take_notifications().is_ok() {
+            assert!(start.elapsed() < TIMEOUT);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn stalled_server_times_out() {
+        let (done, stalled) = channel::<()>();
+        let (client, server) = connect(move |server| {
+            server.hello();
+            server.expect(Request::GetCurrentPreset);
+            let _ = stalled.recv();
+        });
+        let start = Instant::now();
+        let error = client.unwrap().current_preset().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < TIMEOUT * 2);
+        done.send(()).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn dropping_client_stops_reader() {
+        let (done, open) = channel::<()>();
+        let (client, server, reader_dropped) = connect_with_drop_signal(move |server| {
+            server.hello();
+            let _ = open.recv();
+        });
+        let client = client.unwrap();
+        assert_eq!(reader_dropped.try_recv(), Err(TryRecvError::Empty));
+        drop(client);
+        assert_eq!(reader_dropped.try_recv(), Err(TryRecvError::Disconnected));
+        done.send(()).unwrap();
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use crate::config::preset_rule::uniform_override::UniformOverride;
+use crate::util::ini::Ini;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const MANAGED_PRESET_DIR: &str = "reshade_preset_switcher";
+const TECHNIQUES: &str = "Techniques";
+
+// Copies of base presets with rule specific changes, kept in a folder next to the base preset.
+pub fn managed_preset_path(base_preset_path: &Path, rule_id: &str) -> PathBuf {
+    let base_name = base_preset_path
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .unwrap_or_default();
+    base_preset_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(MANAGED_PRESET_DIR)
+        .join(format!("{} - {}.ini", base_name, rule_id))
+}
+
+// Toggles are keyed by technique as stored in the preset, e.g. "DOF@DOF.fx".
+pub fn toggle_techniques(
+    techniques: &[String],
+    technique_toggles: &BTreeMap<String, bool>,
+) -> Vec<String> {
+    let mut result: Vec<String> = techniques
+        .iter()
+        .filter(|technique| technique_toggles.get(*technique) != Some(&false))
+        .cloned()
+        .collect();
+    for (technique, enabled) in technique_toggles {
+        if *enabled && !result.contains(technique) {
+            result.push(technique.clone());
+        }
+    }
+    result
+}
+
+// Written again from the base preset on every activation, the file is only touched when it changed.
+pub fn write_managed_preset(
+    base_preset_path: &Path,
+    managed_preset_path: &Path,
+    technique_toggles: &BTreeMap<String, bool>,
+    uniform_overrides: &[UniformOverride],
+) -> io::Result<()> {
+    let mut preset = Ini::read(base_preset_path)?;
+    if !technique_toggles.is_empty() {
+        let techniques = toggle_techniques(&preset.get_list("", TECHNIQUES), technique_toggles);
+        preset.set("", TECHNIQUES, &techniques.join(","));
+    }
+    for uniform_override in uniform_overrides {
+        preset.set(
+            &uniform_override.effect_file,
+            &uniform_override.key,
+            &uniform_override.value,
+        );
+    }
+    let content = preset.to_string();
+    if fs::read_to_string(managed_preset_path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    if let Some(parent) = managed_preset_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    preset.write(managed_preset_path)
+}
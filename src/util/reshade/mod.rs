@@ -1,65 +1,167 @@
+pub mod ini_detection;
+pub mod ini_watch;
+pub mod ipc;
+pub mod managed_preset;
+pub mod preset_discovery;
+pub mod preset_path;
+pub mod shortcut_repair;
+pub mod switch_backend;
+pub mod transition;
+
 use crate::addon::Addon;
-use crate::context::reshade_context::key_combination::{trigger_key_combination, KeyCombination};
+use crate::context::game_state::GameState;
+use crate::context::reshade_context::key_combination::KeyCombination;
+use crate::context::reshade_context::preset::ReshadePreset;
 use crate::context::reshade_context::ReshadeContext;
-use crate::util::error;
-use crate::util::true_if_1;
+use crate::util::game_has_focus;
+use crate::util::ini::Ini;
+use crate::util::reshade::preset_discovery::INPUT_SECTION;
+use crate::util::reshade::preset_path::PresetPaths;
+use crate::util::reshade::shortcut_repair::RepairState;
+use crate::util::reshade::switch_backend::ini_write::PRESET_PATH;
+use crate::util::reshade::switch_backend::{switch_backends, switch_with_backends};
 use bimap::BiMap;
+use chrono::Local;
 use function_name::named;
-use regex::Regex;
+use log::{debug, error, info, warn};
+use nexus::paths::get_game_dir;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const GENERAL_SECTION: &str = "GENERAL";
+pub const PRESET_SHORTCUT_KEYS: &str = "PresetShortcutKeys";
+pub const PRESET_SHORTCUT_PATHS: &str = "PresetShortcutPaths";
+const MAX_SHORTCUT_REPAIR_REAPPLIES: u32 = 3;
 
-pub fn load_reshade_context(reshade_ini_path: &PathBuf) {
+pub fn load_reshade_context(reshade_ini_path: &Path) {
     if let Ok(content) = fs::read_to_string(reshade_ini_path) {
-        load_active_preset_path(&content);
-        load_presets(&content);
-        Addon::lock().config.reshade.ini_path = reshade_ini_path.clone();
+        Addon::lock()
+            .context
+            .reshade
+            .ini_watch
+            .loaded(reshade_ini_path, &content);
+        apply_reshade_ini(reshade_ini_path, &Ini::parse(&content));
     }
 }
 
-fn load_active_preset_path(content: &str) {
-    let re = Regex::new(r"(?m)^PresetPath=(.*)$").unwrap();
-    if let Some(captures) = re.captures(content) {
-        let preset_path = PathBuf::from(&captures[1].trim());
-        Addon::lock().context.reshade.active_preset_path = preset_path;
+// Called on every background tick, the file is only parsed again when its content changed.
+pub fn reload_reshade_context_if_changed(reshade_ini_path: &Path) {
+    let mut ini_watch = Addon::lock().context.reshade.ini_watch.clone();
+    let changed_content = ini_watch.poll(reshade_ini_path);
+    let check_preset_files = ini_watch.preset_files_check_due();
+    Addon::lock().context.reshade.ini_watch = ini_watch;
+    if let Some(content) = changed_content {
+        apply_reshade_ini(reshade_ini_path, &Ini::parse(&content));
+    } else if check_preset_files {
+        if preset_existence_changed() {
+            load_reshade_context(reshade_ini_path);
+        } else {
+            load_preset_files();
+        }
+    }
+}
+
+// Preset files deleted or restored while the game runs change which keybinds are valid.
+#[named]
+fn preset_existence_changed() -> bool {
+    let mut preset_paths = Addon::lock().context.reshade.preset_paths.clone();
+    if !preset_paths.refresh_existing() {
+        return false;
     }
+    debug!("[{}] Preset files were added or removed", function_name!());
+    Addon::lock().context.reshade.preset_paths = preset_paths;
+    true
 }
 
-fn load_presets(content: &str) {
+fn apply_reshade_ini(reshade_ini_path: &Path, reshade_ini: &Ini) {
+    let preset_paths = load_preset_paths(reshade_ini_path, reshade_ini);
+    Addon::lock().context.reshade.preset_paths = preset_paths;
+    load_active_preset_path(reshade_ini);
+    load_presets(reshade_ini);
+    load_reload_key(reshade_ini);
+    normalize_configured_preset_paths();
+    load_preset_files();
+    check_shortcut_repair(reshade_ini);
+    Addon::lock().config.reshade.ini_path = reshade_ini_path.to_path_buf();
+}
+
+fn preset_base_paths(reshade_ini_path: &Path) -> Vec<PathBuf> {
+    reshade_ini_path
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(get_game_dir())
+        .collect()
+}
+
+// Paths are resolved once per load, the render thread looks them up every frame.
+fn load_preset_paths(reshade_ini_path: &Path, reshade_ini: &Ini) -> PresetPaths {
+    let mut preset_paths = PresetPaths::new(preset_base_paths(reshade_ini_path));
+    let ini_paths = reshade_ini
+        .get(GENERAL_SECTION, PRESET_PATH)
+        .map(String::from)
+        .into_iter()
+        .chain(reshade_ini.get_list(GENERAL_SECTION, PRESET_SHORTCUT_PATHS));
+    for ini_path in ini_paths {
+        preset_paths.add_spelling(&ini_path);
+    }
+    let configured_paths: Vec<PathBuf> = {
+        let addon = Addon::lock();
+        addon
+            .config
+            .preset_rules
+            .iter()
+            .map(|rule| rule.preset_path.clone())
+            .chain(
+                [GameState::CharacterSelect, GameState::LoadingScreen]
+                    .iter()
+                    .filter_map(|game_state| addon.config.game_state_presets.get(game_state))
+                    .cloned(),
+            )
+            .collect()
+    };
+    for configured_path in configured_paths {
+        preset_paths.add(&configured_path);
+    }
+    preset_paths
+}
+
+fn load_active_preset_path(reshade_ini: &Ini) {
+    if let Some(preset_path) = reshade_ini.get(GENERAL_SECTION, PRESET_PATH) {
+        let mut addon = Addon::lock();
+        let preset_path = addon.context.reshade.resolve(Path::new(preset_path));
+        addon.context.reshade.active_preset_path = preset_path;
+    }
+}
+
+fn load_presets(reshade_ini: &Ini) {
+    let reshade_context = Addon::lock().context.reshade.clone();
     let mut invalid_reshade_preset_configuration = false;
     let mut preset_shortcuts: BiMap<KeyCombination, PathBuf> = BiMap::new();
 
-    let paths_re = Regex::new(r"(?m)^PresetShortcutPaths=(.*)$").unwrap();
-    let keys_re = Regex::new(r"(?m)^PresetShortcutKeys=(.*)$").unwrap();
-    if let Some(paths_captures) = paths_re.captures(content) {
-        if let Some(keys_captures) = keys_re.captures(content) {
-            let paths: Vec<PathBuf> = paths_captures[1]
-                .trim()
-                .split(',')
-                .filter(|s| !s.is_empty())
-                .map(PathBuf::from)
-                .collect();
-            let keys: Vec<String> = keys_captures[1]
-                .trim()
-                .split(',')
-                .filter(|s| !s.is_empty())
-                .map(String::from)
+    if reshade_ini
+        .get(GENERAL_SECTION, PRESET_SHORTCUT_PATHS)
+        .is_some()
+    {
+        if reshade_ini
+            .get(GENERAL_SECTION, PRESET_SHORTCUT_KEYS)
+            .is_some()
+        {
+            let paths: Vec<PathBuf> = reshade_ini
+                .get_list(GENERAL_SECTION, PRESET_SHORTCUT_PATHS)
+                .iter()
+                .map(|path| reshade_context.resolve(Path::new(path)))
                 .collect();
+            let keys = reshade_ini.get_list(GENERAL_SECTION, PRESET_SHORTCUT_KEYS);
 
             for (i, chunk) in keys.chunks(4).enumerate() {
                 if let Some(path) = paths.get(i) {
-                    if path.exists() {
-                        let key_code = chunk.first().unwrap().as_str().to_string();
-                        let mut key_combination = KeyCombination {
-                            key_code,
-                            ctrl: false,
-                            shift: false,
-                            alt: false,
-                        };
-                        key_combination.ctrl = chunk.get(1).map(true_if_1()).unwrap_or(false);
-                        key_combination.shift = chunk.get(2).map(true_if_1()).unwrap_or(false);
-                        key_combination.alt = chunk.get(3).map(true_if_1()).unwrap_or(false);
-                        preset_shortcuts.insert(key_combination, path.clone());
+                    if reshade_context.preset_exists(path) {
+                        if let Some(key_combination) = KeyCombination::from_reshade_values(chunk) {
+                            preset_shortcuts.insert(key_combination, path.clone());
+                        }
                     } else {
                         invalid_reshade_preset_configuration = true;
                     }
@@ -74,14 +176,168 @@ fn load_presets(content: &str) {
         .invalid_reshade_preset_configuration = invalid_reshade_preset_configuration;
 }
 
+// Key code 0 means no key is assigned.
+fn load_reload_key(reshade_ini: &Ini) {
+    let reload_key =
+        KeyCombination::from_reshade_values(&reshade_ini.get_list(INPUT_SECTION, "KeyReload"))
+            .filter(|key_combination| key_combination.is_valid());
+    Addon::lock().context.reshade.reload_key = reload_key;
+}
+
+// Rules saved before paths were resolved may still hold paths as written in ReShade.ini.
+fn normalize_configured_preset_paths() {
+    let addon = &mut *Addon::lock();
+    let reshade_context = &addon.context.reshade;
+    for rule in addon.config.preset_rules.iter_mut() {
+        rule.preset_path = reshade_context.resolve(&rule.preset_path);
+    }
+    for game_state in [GameState::CharacterSelect, GameState::LoadingScreen] {
+        if let Some(Some(preset_path)) = addon.config.game_state_presets.get_mut(&game_state) {
+            *preset_path = reshade_context.resolve(preset_path);
+        }
+    }
+}
+
+// Preset files are only read again when they were modified.
+fn load_preset_files() {
+    let reshade_context = Addon::lock().context.reshade.clone();
+    let mut presets = HashMap::new();
+    let mut changed_presets = Vec::new();
+    for preset_path in reshade_context.preset_shortcuts.right_values() {
+        let modified = fs::metadata(preset_path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        match reshade_context.presets.get(preset_path) {
+            Some(preset) if preset.modified == modified => {
+                presets.insert(preset_path.clone(), preset.clone());
+            }
+            _ => match ReshadePreset::read(preset_path) {
+                Ok(preset) => {
+                    presets.insert(preset_path.clone(), preset);
+                    changed_presets.push(preset_path.clone());
+                }
+                Err(e) => warn!("Could not read preset [{}]: {}", preset_path.display(), e),
+            },
+        }
+    }
+    Addon::lock().context.reshade.presets = presets;
+    sync_preset_variants(&changed_presets);
+}
+
+// Variants generated before are written again when their base preset changed on disk.
+#[named]
+fn sync_preset_variants(changed_presets: &[PathBuf]) {
+    let (preset_rules, reshade_context) = {
+        let addon = Addon::lock();
+        (
+            addon.config.preset_rules.clone(),
+            addon.context.reshade.clone(),
+        )
+    };
+    for rule in preset_rules.iter().filter(|rule| rule.has_variant()) {
+        let base_preset_path = reshade_context.resolve(&rule.preset_path);
+        if !changed_presets.contains(&base_preset_path)
+            || !rule.activation_preset_path(&reshade_context).exists()
+        {
+            continue;
+        }
+        match rule.write_variant(&reshade_context) {
+            Ok(variant_path) => debug!(
+                "[{}] Variant [{}] in sync with base preset",
+                function_name!(),
+                variant_path.display()
+            ),
+            Err(e) => warn!(
+                "[{}] Could not update variant of rule [{}]: {}",
+                function_name!(),
+                rule.rule_name,
+                e
+            ),
+        }
+    }
+}
+
+// ReShade keeps its settings in memory and may save the old keybinds over a repair.
+// The repair is written again a few times before the user is asked to apply it after a restart.
+// Keybinds changed to other values are left alone and the repair is no longer watched.
+#[named]
+fn check_shortcut_repair(reshade_ini: &Ini) {
+    let (repair, reapplied) = {
+        let mut addon = Addon::lock();
+        let reshade_context = &mut addon.context.reshade;
+        let Some(repair) = &reshade_context.applied_shortcut_repair else {
+            return;
+        };
+        match repair.state_in(reshade_ini) {
+            RepairState::Applied => return,
+            RepairState::Overwritten => (repair.clone(), reshade_context.shortcut_repair_reapplied),
+            RepairState::Changed => {
+                info!(
+                    "[{}] Preset keybinds were changed since the repair, keeping them",
+                    function_name!()
+                );
+                reshade_context.applied_shortcut_repair = None;
+                return;
+            }
+        }
+    };
+    warn!(
+        "[{}] Repaired preset keybinds were overwritten by ReShade",
+        function_name!()
+    );
+    if reapplied < MAX_SHORTCUT_REPAIR_REAPPLIES {
+        match repair.apply_again() {
+            Ok(()) => {
+                Addon::lock().context.reshade.shortcut_repair_reapplied = reapplied + 1;
+                return;
+            }
+            Err(e) => error!(
+                "[{}] Could not repair ReShade.ini again: {}",
+                function_name!(),
+                e
+            ),
+        }
+    }
+    let mut addon = Addon::lock();
+    addon.context.reshade.applied_shortcut_repair = None;
+    addon.context.reshade.shortcut_repair_overwritten = true;
+}
+
+pub fn backup_file(path: &Path) -> io::Result<PathBuf> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(format!(".{}.bak", Local::now().format("%Y%m%d-%H%M%S")));
+    let backup_path = PathBuf::from(backup_path);
+    fs::copy(path, &backup_path)?;
+    Ok(backup_path)
+}
+
+// The companion add-on reports presets changed in ReShade directly, without waiting for ReShade.ini.
+#[named]
+pub fn update_active_preset_from_ipc() {
+    let ipc = Addon::lock().context.reshade.ipc.clone();
+    if let Some(preset_path) = ipc.take_notifications().last() {
+        let mut addon = Addon::lock();
+        let preset_path = addon.context.reshade.resolve(preset_path);
+        debug!(
+            "[{}] Companion add-on reported preset [{}]",
+            function_name!(),
+            preset_path.display()
+        );
+        addon.context.reshade.active_preset_path = preset_path;
+    }
+}
+
 #[named]
-pub fn switch_to_preset(preset_path: &PathBuf, context: &ReshadeContext) {
-    if let Some(key_combination) = context.preset_shortcuts.get_by_right(preset_path) {
-        trigger_key_combination(key_combination);
-    } else {
+pub fn switch_to_preset(preset_path: &Path, context: &ReshadeContext) {
+    let preset_path = context.resolve(preset_path);
+    let reshade_config = Addon::lock().config.reshade.clone();
+    let backends = switch_backends(&reshade_config);
+    if let Err(e) = switch_with_backends(&backends, &preset_path, context, game_has_focus()) {
         error!(
-            "[{}] Could not find key combination for preset",
-            function_name!()
+            "[{}] Could not switch to preset [{}]: {}",
+            function_name!(),
+            preset_path.display(),
+            e
         );
     }
 }
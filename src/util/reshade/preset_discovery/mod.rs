@@ -0,0 +1,148 @@
+use crate::context::reshade_context::key_combination::KeyCombination;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::ini::Ini;
+use crate::util::reshade::{GENERAL_SECTION, PRESET_SHORTCUT_KEYS, PRESET_SHORTCUT_PATHS};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const INPUT_SECTION: &str = "INPUT";
+// F13 to F24 are missing on most keyboards, so nobody presses them by accident.
+const PREFERRED_KEY_CODES: [u32; 12] = [124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135];
+const MODIFIERS: [(bool, bool, bool); 7] = [
+    (false, false, false),
+    (true, false, false),
+    (false, true, false),
+    (false, false, true),
+    (true, true, false),
+    (true, false, true),
+    (false, true, true),
+];
+
+// Presets are searched next to the active preset and the presets that already have keybinds.
+pub fn discover_presets(reshade_ini: &Ini, reshade_context: &ReshadeContext) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = vec![];
+    let shortcut_paths: Vec<PathBuf> = reshade_ini
+        .get_list(GENERAL_SECTION, PRESET_SHORTCUT_PATHS)
+        .iter()
+        .map(|preset_path| reshade_context.resolve(Path::new(preset_path)))
+        .collect();
+    let preset_paths = reshade_ini
+        .get(GENERAL_SECTION, "PresetPath")
+        .map(|preset_path| reshade_context.resolve(Path::new(preset_path)))
+        .into_iter()
+        .chain(shortcut_paths.iter().cloned());
+    for preset_path in preset_paths {
+        if let Some(folder) = preset_path.parent() {
+            if !folders.iter().any(|f| f == folder) {
+                folders.push(folder.to_path_buf());
+            }
+        }
+    }
+
+    let mut presets: Vec<PathBuf> = folders
+        .iter()
+        .flat_map(|folder| presets_in_folder(folder))
+        .filter(|preset| !shortcut_paths.contains(preset))
+        .collect();
+    presets.sort();
+    presets.dedup();
+    presets
+}
+
+fn presets_in_folder(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| folder.join(entry.file_name()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("ini"))
+        })
+        .filter(|path| is_preset(path))
+        .collect()
+}
+
+fn is_preset(path: &Path) -> bool {
+    Ini::read(path).is_ok_and(|preset| preset.get("", "Techniques").is_some())
+}
+
+// Key combinations already used by presets or other ReShade keybinds are skipped. Preset keys are
+// taken from ReShade.ini directly, as the context lacks the ones it could not load.
+pub fn assign_key_combinations(
+    presets: &[PathBuf],
+    reshade_ini: &Ini,
+) -> Vec<(KeyCombination, PathBuf)> {
+    let mut used: HashSet<KeyCombination> = reshade_ini
+        .get_list(GENERAL_SECTION, PRESET_SHORTCUT_KEYS)
+        .chunks(4)
+        .filter_map(KeyCombination::from_reshade_values)
+        .collect();
+    if let Some(input_section) = reshade_ini.section(INPUT_SECTION) {
+        used.extend(
+            input_section
+                .entries()
+                .filter(|(key, _)| key.starts_with("Key"))
+                .filter_map(|(_, value)| {
+                    let values: Vec<String> = value.split(',').map(String::from).collect();
+                    KeyCombination::from_reshade_values(&values)
+                }),
+        );
+    }
+
+    let mut free_key_combinations = MODIFIERS
+        .iter()
+        .flat_map(|&(ctrl, shift, alt)| {
+            PREFERRED_KEY_CODES
+                .iter()
+                .map(move |key_code| KeyCombination {
+                    key_code: key_code.to_string(),
+                    ctrl,
+                    shift,
+                    alt,
+                })
+        })
+        .filter(|key_combination| !used.contains(key_combination));
+    presets
+        .iter()
+        .map_while(|preset| {
+            free_key_combinations
+                .next()
+                .map(|key_combination| (key_combination, preset.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_every_key_in_reshade_ini() {
+        // 124 with Ctrl is not loaded by the addon, as its preset path is missing.
+        let reshade_ini = Ini::parse(
+            "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0,125,0,0,0,124,1,0,0\n\
+            PresetShortcutPaths=Day.ini,Night.ini,Missing.ini\n\
+            [INPUT]\n\
+            KeyEffects=126,0,0,0\n\
+            KeyReload=127,0,0,0\n",
+        );
+        let presets: Vec<PathBuf> = (0..13)
+            .map(|i| PathBuf::from(format!("Preset{}.ini", i)))
+            .collect();
+        let assignments = assign_key_combinations(&presets, &reshade_ini);
+        let key_combinations: Vec<String> = assignments
+            .iter()
+            .map(|(key_combination, _)| key_combination.to_reshade_values().join(","))
+            .collect();
+        assert_eq!(key_combinations.len(), 13);
+        assert_eq!(key_combinations[0], "128,0,0,0");
+        assert_eq!(key_combinations[7], "135,0,0,0");
+        assert_eq!(key_combinations[8], "125,1,0,0");
+        assert!(!key_combinations.contains(&"124,1,0,0".to_string()));
+        assert_eq!(assignments[12].1, PathBuf::from("Preset12.ini"));
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+// Paths from ReShade.ini and the configuration resolved once the ini is loaded,
+// so the render thread can look them up without touching the file system.
+#[derive(Debug, Clone, Default)]
+pub struct PresetPaths {
+    base_paths: Vec<PathBuf>,
+    resolved: HashMap<String, PathBuf>,
+    spellings: HashMap<PathBuf, String>,
+    existing: HashSet<PathBuf>,
+}
+
+impl PresetPaths {
+    pub fn new(base_paths: Vec<PathBuf>) -> PresetPaths {
+        PresetPaths {
+            base_paths,
+            ..Default::default()
+        }
+    }
+
+    // Paths read from ReShade.ini keep their spelling for writing them back.
+    pub fn add_spelling(&mut self, spelling: &str) -> PathBuf {
+        let resolved = self.add(Path::new(spelling));
+        self.spellings
+            .entry(resolved.clone())
+            .or_insert_with(|| spelling.to_string());
+        resolved
+    }
+
+    pub fn add(&mut self, path: &Path) -> PathBuf {
+        if let Some(resolved) = self.resolved.get(&comparison_key(path)) {
+            return resolved.clone();
+        }
+        let resolved = resolve_preset_path(path, &self.base_paths);
+        if resolved.exists() {
+            self.existing.insert(resolved.clone());
+        }
+        self.resolved.insert(comparison_key(path), resolved.clone());
+        self.resolved
+            .insert(comparison_key(&resolved), resolved.clone());
+        resolved
+    }
+
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        if path.as_os_str().is_empty() {
+            return PathBuf::new();
+        }
+        self.resolved
+            .get(&comparison_key(path))
+            .cloned()
+            .unwrap_or_else(|| match self.base_paths.first() {
+                Some(base_path) if !is_absolute(path) => lexical_normalize(&base_path.join(path)),
+                _ => lexical_normalize(path),
+            })
+    }
+
+    pub fn exists(&self, path: &Path) -> bool {
+        self.existing.contains(&self.resolve(path))
+    }
+
+    // Preset files may be created or deleted while the game runs. Returns whether anything changed.
+    pub fn refresh_existing(&mut self) -> bool {
+        let existing: HashSet<PathBuf> = self
+            .resolved
+            .values()
+            .filter(|path| path.exists())
+            .cloned()
+            .collect();
+        let changed = existing != self.existing;
+        self.existing = existing;
+        changed
+    }
+
+    // Spelling to write into ReShade.ini. Unknown paths are written relative to its directory when inside it.
+    pub fn spelling(&self, path: &Path) -> String {
+        let resolved = self.resolve(path);
+        if let Some(spelling) = self.spellings.get(&resolved) {
+            return spelling.clone();
+        }
+        self.base_paths
+            .first()
+            .and_then(|base_path| relative_spelling(&resolved, base_path))
+            .unwrap_or_else(|| resolved.to_string_lossy().to_string())
+    }
+}
+
+// ReShade resolves relative paths against its base path. That is usually the directory of
+// ReShade.ini, the game directory is tried as well for setups that override the base path.
+pub fn resolve_preset_path(path: &Path, base_paths: &[PathBuf]) -> PathBuf {
+    if path.as_os_str().is_empty() || is_absolute(path) {
+        return normalize_path(path);
+    }
+    let candidates: Vec<PathBuf> = base_paths
+        .iter()
+        .map(|base_path| normalize_path(&base_path.join(path)))
+        .collect();
+    candidates
+        .iter()
+        .find(|candidate| candidate.exists())
+        .or(candidates.first())
+        .cloned()
+        .unwrap_or_else(|| normalize_path(path))
+}
+
+// Existing files are canonicalized to get the real casing, other paths are cleaned up lexically.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    if path.as_os_str().is_empty() {
+        return PathBuf::new();
+    }
+    let path = lexical_normalize(path);
+    match fs::canonicalize(&path) {
+        Ok(canonical_path) => strip_verbatim_prefix(canonical_path),
+        Err(_) => path,
+    }
+}
+
+// ReShade.ini may mix both separators, they are treated alike on every platform.
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let path = path.to_string_lossy();
+    let (prefix, rest) = split_prefix(&path);
+    let mut parts: Vec<&str> = vec![];
+    for part in rest.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => match parts.last() {
+                Some(&last) if last != ".." => {
+                    parts.pop();
+                }
+                _ if prefix.is_empty() => parts.push(part),
+                _ => {}
+            },
+            _ => parts.push(part),
+        }
+    }
+    PathBuf::from(format!("{}{}", prefix, parts.join(MAIN_SEPARATOR_STR)))
+}
+
+// Root, drive or UNC prefix with separators unified.
+fn split_prefix(path: &str) -> (String, &str) {
+    let is_separator = |c: char| c == '/' || c == '\\';
+    let mut chars = path.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(first), Some(second), _) if is_separator(first) && is_separator(second) => {
+            (MAIN_SEPARATOR_STR.repeat(2), &path[2..])
+        }
+        (Some(first), _, _) if is_separator(first) => (MAIN_SEPARATOR_STR.to_string(), &path[1..]),
+        (Some(drive), Some(':'), Some(third))
+            if drive.is_ascii_alphabetic() && is_separator(third) =>
+        {
+            (format!("{}:{}", drive, MAIN_SEPARATOR_STR), &path[3..])
+        }
+        (Some(drive), Some(':'), _) if drive.is_ascii_alphabetic() => {
+            (format!("{}:", drive), &path[2..])
+        }
+        _ => (String::new(), path),
+    }
+}
+
+fn is_absolute(path: &Path) -> bool {
+    path.is_absolute() || !split_prefix(&path.to_string_lossy()).0.is_empty()
+}
+
+// Windows paths are compared ignoring case and separator style.
+fn comparison_key(path: &Path) -> String {
+    lexical_normalize(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+        .to_lowercase()
+}
+
+fn relative_spelling(path: &Path, base_path: &Path) -> Option<String> {
+    let path = lexical_normalize(path).to_string_lossy().to_string();
+    let base_path = lexical_normalize(base_path).to_string_lossy().to_string();
+    let prefix = path.get(..base_path.len())?;
+    let relative_path = path.get(base_path.len()..)?;
+    if comparison_key(Path::new(prefix)) != comparison_key(Path::new(&base_path))
+        || !relative_path.starts_with(['/', '\\'])
+    {
+        return None;
+    }
+    Some(format!(".{}", relative_path.replace('/', "\\")))
+}
+
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    if let Some(unc_path) = path_str.strip_prefix(VERBATIM_UNC_PREFIX) {
+        PathBuf::from(format!(r"\\{}", unc_path))
+    } else if let Some(local_path) = path_str.strip_prefix(VERBATIM_PREFIX) {
+        PathBuf::from(local_path)
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test_dir::TestDir;
+
+    fn preset_dir(name: &str) -> (TestDir, PathBuf) {
+        let test_dir = TestDir::new(&format!("preset_path_{}", name));
+        let dir = test_dir.path();
+        fs::create_dir_all(dir.join("reshade-presets")).unwrap();
+        fs::create_dir_all(dir.join("ReShade")).unwrap();
+        fs::write(dir.join("reshade-presets").join("Day.ini"), "").unwrap();
+        let dir = normalize_path(dir);
+        (test_dir, dir)
+    }
+
+    #[test]
+    fn lexical_normalize_windows_paths() {
+        let sep = MAIN_SEPARATOR_STR;
+        let cases = [
+            (
+                r".\reshade-presets\Day.ini",
+                format!("reshade-presets{sep}Day.ini"),
+            ),
+            (r"..\presets\Day.ini", format!("..{sep}presets{sep}Day.ini")),
+            (r"presets/sub\..\Day.ini", format!("presets{sep}Day.ini")),
+            (r".\.\a\.\b\\c.ini", format!("a{sep}b{sep}c.ini")),
+            (
+                r"C:\Games\GW2\..\GW2\.\Day.ini",
+                format!("C:{sep}Games{sep}GW2{sep}Day.ini"),
+            ),
+            (
+                r"C:/Games\GW2/Day.ini",
+                format!("C:{sep}Games{sep}GW2{sep}Day.ini"),
+            ),
+            (
+                r"\\server\share\.\Day.ini",
+                format!("{sep}{sep}server{sep}share{sep}Day.ini"),
+            ),
+            (r"C:\..\Day.ini", format!("C:{sep}Day.ini")),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                lexical_normalize(Path::new(path)),
+                PathBuf::from(expected),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn comparison_ignores_case_and_separators() {
+        assert_eq!(
+            comparison_key(Path::new(r"C:\Games\GW2\reshade-presets\Day.ini")),
+            comparison_key(Path::new(r"c:/games/gw2/./Reshade-Presets\day.INI"))
+        );
+        assert_ne!(
+            comparison_key(Path::new(r"C:\Games\Day.ini")),
+            comparison_key(Path::new(r"C:\Games\Night.ini"))
+        );
+    }
+
+    #[test]
+    fn resolves_relative_paths_against_ini_directory() {
+        let (_test_dir, dir) = preset_dir("resolve");
+        let expected = dir.join("reshade-presets").join("Day.ini");
+        let base_paths = vec![dir.join("ReShade"), dir.clone()];
+        for path in [
+            r".\reshade-presets\Day.ini",
+            r"reshade-presets/Day.ini",
+            r"..\reshade-presets\Day.ini",
+            r".\ReShade\..\reshade-presets/Day.ini",
+        ] {
+            assert_eq!(
+                resolve_preset_path(Path::new(path), &base_paths),
+                expected,
+                "{}",
+                path
+            );
+        }
+        assert_eq!(
+            resolve_preset_path(Path::new(r".\Missing.ini"), &base_paths),
+            dir.join("ReShade").join("Missing.ini")
+        );
+    }
+
+    #[test]
+    fn preset_paths_resolve_from_cache() {
+        let (_test_dir, dir) = preset_dir("cache");
+        let preset = dir.join("reshade-presets").join("Day.ini");
+        let mut preset_paths = PresetPaths::new(vec![dir.clone()]);
+        assert_eq!(
+            preset_paths.add_spelling(r".\reshade-presets\Day.ini"),
+            preset
+        );
+        let upper_case = PathBuf::from(preset.to_string_lossy().to_uppercase());
+        assert_eq!(preset_paths.resolve(&upper_case), preset);
+        assert_eq!(
+            preset_paths.resolve(Path::new(r"reshade-presets/DAY.ini")),
+            preset
+        );
+        assert!(preset_paths.exists(&preset));
+
+        fs::remove_file(&preset).unwrap();
+        assert!(preset_paths.exists(&preset));
+        assert!(preset_paths.refresh_existing());
+        assert!(!preset_paths.exists(&preset));
+        assert!(!preset_paths.refresh_existing());
+    }
+
+    #[test]
+    fn keeps_original_spelling() {
+        let (_test_dir, dir) = preset_dir("spelling");
+        let mut preset_paths = PresetPaths::new(vec![dir.clone()]);
+        preset_paths.add_spelling(r".\reshade-presets\Day.ini");
+        assert_eq!(
+            preset_paths.spelling(&dir.join("reshade-presets").join("Day.ini")),
+            r".\reshade-presets\Day.ini"
+        );
+        assert_eq!(
+            preset_paths.spelling(&dir.join("reshade-presets").join("Night.ini")),
+            r".\reshade-presets\Night.ini"
+        );
+        let outside = dir.parent().unwrap().join("Other.ini");
+        assert_eq!(
+            preset_paths.spelling(&outside),
+            outside.to_string_lossy().to_string()
+        );
+    }
+}
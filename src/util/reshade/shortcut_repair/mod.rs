@@ -0,0 +1,288 @@
+use crate::context::reshade_context::key_combination::KeyCombination;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::ini::Ini;
+use crate::util::reshade::{
+    backup_file, GENERAL_SECTION, PRESET_SHORTCUT_KEYS, PRESET_SHORTCUT_PATHS,
+};
+use function_name::named;
+use log::info;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct ShortcutRepair {
+    pub ini_path: PathBuf,
+    pub changes: Vec<IniChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IniChange {
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: String,
+}
+
+impl ShortcutRepair {
+    pub fn prepare(
+        ini_path: &Path,
+        reshade_context: &ReshadeContext,
+    ) -> io::Result<ShortcutRepair> {
+        Self::prepare_with_assignments(ini_path, reshade_context, &[])
+    }
+
+    // New keybinds are appended after the ones already in ReShade.ini.
+    pub fn prepare_with_assignments(
+        ini_path: &Path,
+        reshade_context: &ReshadeContext,
+        assignments: &[(KeyCombination, PathBuf)],
+    ) -> io::Result<ShortcutRepair> {
+        let reshade_ini = Ini::read(ini_path)?;
+        let assignments: Vec<(KeyCombination, String)> = assignments
+            .iter()
+            .map(|(key_combination, path)| {
+                (key_combination.clone(), reshade_context.ini_spelling(path))
+            })
+            .collect();
+        let (keys, paths) = repaired_shortcut_lists(
+            &reshade_ini,
+            |path| reshade_context.preset_exists(Path::new(path)),
+            &assignments,
+        );
+        let changes = [
+            (PRESET_SHORTCUT_KEYS, keys.join(",")),
+            (PRESET_SHORTCUT_PATHS, paths.join(",")),
+        ]
+        .into_iter()
+        .map(|(key, new_value)| IniChange {
+            key: key.to_string(),
+            old_value: reshade_ini.get(GENERAL_SECTION, key).map(String::from),
+            new_value,
+        })
+        .filter(|change| change.old_value.as_deref().unwrap_or("") != change.new_value)
+        .collect();
+        Ok(ShortcutRepair {
+            ini_path: ini_path.to_path_buf(),
+            changes,
+        })
+    }
+
+    // File is read again, so settings saved by ReShade since the preview are kept.
+    #[named]
+    pub fn apply(&self) -> io::Result<PathBuf> {
+        let backup_path = backup_file(&self.ini_path)?;
+        self.write()?;
+        info!(
+            "[{}] Repaired preset keybinds in [{}], backup saved to [{}]",
+            function_name!(),
+            self.ini_path.display(),
+            backup_path.display()
+        );
+        Ok(backup_path)
+    }
+
+    // Used when ReShade saved its old settings over the repair, the first backup is kept.
+    #[named]
+    pub fn apply_again(&self) -> io::Result<()> {
+        self.write()?;
+        info!(
+            "[{}] Repaired preset keybinds in [{}] again",
+            function_name!(),
+            self.ini_path.display()
+        );
+        Ok(())
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let mut reshade_ini = Ini::read(&self.ini_path)?;
+        for change in &self.changes {
+            reshade_ini.set(GENERAL_SECTION, &change.key, &change.new_value);
+        }
+        reshade_ini.write(&self.ini_path)
+    }
+
+    // ReShade saves the keybinds it still has in memory, which are the values from before the repair.
+    // Any other value was changed on purpose, for example in the ReShade overlay.
+    pub fn state_in(&self, reshade_ini: &Ini) -> RepairState {
+        let values: Vec<Option<&str>> = self
+            .changes
+            .iter()
+            .map(|change| reshade_ini.get(GENERAL_SECTION, &change.key))
+            .collect();
+        let is_new =
+            |(change, value): (&IniChange, &Option<&str>)| *value == Some(&change.new_value);
+        let is_old =
+            |(change, value): (&IniChange, &Option<&str>)| *value == change.old_value.as_deref();
+        if self.changes.iter().zip(&values).all(is_new) {
+            RepairState::Applied
+        } else if self
+            .changes
+            .iter()
+            .zip(&values)
+            .all(|change_value| is_new(change_value) || is_old(change_value))
+        {
+            RepairState::Overwritten
+        } else {
+            RepairState::Changed
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepairState {
+    Applied,
+    Overwritten,
+    Changed,
+}
+
+// Entries are kept as written unless their preset file is missing, so keybinds the addon
+// cannot parse stay untouched. Paths without keys get an unassigned key to stay aligned.
+fn repaired_shortcut_lists(
+    reshade_ini: &Ini,
+    preset_exists: impl Fn(&str) -> bool,
+    assignments: &[(KeyCombination, String)],
+) -> (Vec<String>, Vec<String>) {
+    let keys = reshade_ini.get_list(GENERAL_SECTION, PRESET_SHORTCUT_KEYS);
+    let mut key_chunks = keys.chunks(4);
+    let mut repaired_keys = vec![];
+    let mut repaired_paths = vec![];
+    for path in reshade_ini.get_list(GENERAL_SECTION, PRESET_SHORTCUT_PATHS) {
+        let key_chunk = key_chunks.next().unwrap_or_default();
+        if !preset_exists(&path) {
+            continue;
+        }
+        repaired_keys
+            .extend((0..4).map(|i| key_chunk.get(i).cloned().unwrap_or_else(|| "0".to_string())));
+        repaired_paths.push(path);
+    }
+    for (key_combination, path) in assignments {
+        repaired_keys.extend(key_combination.to_reshade_values());
+        repaired_paths.push(path.clone());
+    }
+    (repaired_keys, repaired_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists(content: &str, assignments: &[(KeyCombination, String)]) -> (String, String) {
+        let (keys, paths) = repaired_shortcut_lists(
+            &Ini::parse(content),
+            |path| !path.contains("Missing"),
+            assignments,
+        );
+        (keys.join(","), paths.join(","))
+    }
+
+    #[test]
+    fn keeps_entries_as_written() {
+        let content = "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0,999,1,0,0,124,0,0,0\n\
+            PresetShortcutPaths=.\\Day.ini,..\\presets/Night.ini,.\\day.ini\n";
+        assert_eq!(
+            lists(content, &[]),
+            (
+                "124,0,0,0,999,1,0,0,124,0,0,0".to_string(),
+                ".\\Day.ini,..\\presets/Night.ini,.\\day.ini".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn drops_only_missing_presets() {
+        let content = "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0,125,0,0,0,126,0,1,0\n\
+            PresetShortcutPaths=Day.ini,Missing.ini,Night.ini\n";
+        assert_eq!(
+            lists(content, &[]),
+            (
+                "124,0,0,0,126,0,1,0".to_string(),
+                "Day.ini,Night.ini".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn aligns_keys_and_paths() {
+        let content = "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0,125,1\n\
+            PresetShortcutPaths=Day.ini,Night.ini,Dusk.ini\n";
+        assert_eq!(
+            lists(content, &[]),
+            (
+                "124,0,0,0,125,1,0,0,0,0,0,0".to_string(),
+                "Day.ini,Night.ini,Dusk.ini".to_string()
+            )
+        );
+        let content = "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0,125,0,0,0\n\
+            PresetShortcutPaths=Day.ini\n";
+        assert_eq!(
+            lists(content, &[]),
+            ("124,0,0,0".to_string(), "Day.ini".to_string())
+        );
+    }
+
+    #[test]
+    fn appends_assignments() {
+        let content = "[GENERAL]\n\
+            PresetShortcutKeys=124,0,0,0\n\
+            PresetShortcutPaths=Day.ini\n";
+        let assignment = (
+            KeyCombination {
+                key_code: "125".to_string(),
+                ctrl: true,
+                shift: false,
+                alt: false,
+            },
+            "Night.ini".to_string(),
+        );
+        assert_eq!(
+            lists(content, std::slice::from_ref(&assignment)),
+            (
+                "124,0,0,0,125,1,0,0".to_string(),
+                "Day.ini,Night.ini".to_string()
+            )
+        );
+        assert_eq!(
+            lists("", &[assignment]),
+            ("125,1,0,0".to_string(), "Night.ini".to_string())
+        );
+    }
+
+    #[test]
+    fn tells_reshade_saves_from_user_changes() {
+        let change = |key: &str, old_value: &str, new_value: &str| IniChange {
+            key: key.to_string(),
+            old_value: Some(old_value.to_string()),
+            new_value: new_value.to_string(),
+        };
+        let repair = ShortcutRepair {
+            ini_path: PathBuf::new(),
+            changes: vec![
+                change(PRESET_SHORTCUT_KEYS, "124,0,0,0,125,0,0,0", "124,0,0,0"),
+                change(PRESET_SHORTCUT_PATHS, "Day.ini,Missing.ini", "Day.ini"),
+            ],
+        };
+        let state = |keys: &str, paths: &str| {
+            repair.state_in(&Ini::parse(&format!(
+                "[GENERAL]\nPresetShortcutKeys={}\nPresetShortcutPaths={}\n",
+                keys, paths
+            )))
+        };
+        assert_eq!(state("124,0,0,0", "Day.ini"), RepairState::Applied);
+        assert_eq!(
+            state("124,0,0,0,125,0,0,0", "Day.ini,Missing.ini"),
+            RepairState::Overwritten
+        );
+        assert_eq!(
+            state("124,0,0,0", "Day.ini,Missing.ini"),
+            RepairState::Overwritten
+        );
+        assert_eq!(state("126,0,0,0", "Day.ini"), RepairState::Changed);
+        assert_eq!(
+            state("124,0,0,0,126,0,0,0", "Day.ini,Night.ini"),
+            RepairState::Changed
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_backend::{SwitchBackend, SwitchBackendKind};
+use function_name::named;
+use log::info;
+use std::io;
+use std::path::Path;
+
+// Logs switches instead of performing them, to try out rules without touching ReShade.
+pub struct DryRunBackend;
+
+impl SwitchBackend for DryRunBackend {
+    fn kind(&self) -> SwitchBackendKind {
+        SwitchBackendKind::DryRun
+    }
+
+    fn can_switch(&self, _preset_path: &Path, _context: &ReshadeContext) -> bool {
+        true
+    }
+
+    #[named]
+    fn switch(&self, preset_path: &Path, _context: &ReshadeContext) -> io::Result<()> {
+        info!(
+            "[{}] Dry run, would switch to preset [{}]",
+            function_name!(),
+            preset_path.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::context::reshade_context::key_combination::trigger_key_combination;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::ini::Ini;
+use crate::util::reshade::switch_backend::{SwitchBackend, SwitchBackendKind};
+use crate::util::reshade::transition::set_transition_duration;
+use crate::util::reshade::GENERAL_SECTION;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const PRESET_PATH: &str = "PresetPath";
+
+// ReShade reads its configuration again when effects are reloaded, so the
+// preset written to ReShade.ini becomes active after the reload key is pressed.
+pub struct IniWriteBackend {
+    ini_path: PathBuf,
+    original_transition_duration: Option<String>,
+}
+
+impl IniWriteBackend {
+    pub fn new(ini_path: PathBuf, original_transition_duration: Option<String>) -> IniWriteBackend {
+        IniWriteBackend {
+            ini_path,
+            original_transition_duration,
+        }
+    }
+}
+
+impl SwitchBackend for IniWriteBackend {
+    fn kind(&self) -> SwitchBackendKind {
+        SwitchBackendKind::IniWrite
+    }
+
+    fn can_switch(&self, preset_path: &Path, context: &ReshadeContext) -> bool {
+        context.reload_key.is_some() && self.ini_path.exists() && preset_path.exists()
+    }
+
+    fn switch(&self, preset_path: &Path, context: &ReshadeContext) -> io::Result<()> {
+        let reload_key = context
+            .reload_key
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no ReShade reload key"))?;
+        let mut reshade_ini = Ini::read(&self.ini_path)?;
+        reshade_ini.set(
+            GENERAL_SECTION,
+            PRESET_PATH,
+            &context.ini_spelling(preset_path),
+        );
+        set_transition_duration(
+            &mut reshade_ini,
+            context.transition_duration_ms,
+            self.original_transition_duration.as_deref(),
+        );
+        reshade_ini.write(&self.ini_path)?;
+        trigger_key_combination(reload_key);
+        Ok(())
+    }
+}
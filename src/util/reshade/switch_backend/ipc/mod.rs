@@ -0,0 +1,27 @@
+use crate::addon::Addon;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_backend::{SwitchBackend, SwitchBackendKind};
+use std::io;
+use std::path::Path;
+
+// Switches through a companion ReShade add-on, only available while it is running.
+pub struct IpcBackend;
+
+impl SwitchBackend for IpcBackend {
+    fn kind(&self) -> SwitchBackendKind {
+        SwitchBackendKind::Ipc
+    }
+
+    fn can_switch(&self, _preset_path: &Path, context: &ReshadeContext) -> bool {
+        context.ipc.connect()
+    }
+
+    fn switch(&self, preset_path: &Path, context: &ReshadeContext) -> io::Result<()> {
+        let current_preset = context.ipc.with_client(|client| {
+            client.switch_preset(preset_path)?;
+            client.current_preset()
+        })?;
+        Addon::lock().context.reshade.active_preset_path = context.resolve(&current_preset);
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::context::reshade_context::key_combination::trigger_key_combination;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_backend::{SwitchBackend, SwitchBackendKind};
+use std::io;
+use std::path::Path;
+
+pub struct KeypressBackend;
+
+impl SwitchBackend for KeypressBackend {
+    fn kind(&self) -> SwitchBackendKind {
+        SwitchBackendKind::Keypress
+    }
+
+    fn can_switch(&self, preset_path: &Path, context: &ReshadeContext) -> bool {
+        context
+            .preset_shortcuts
+            .get_by_right(preset_path)
+            .is_some_and(|key_combination| key_combination.is_valid())
+    }
+
+    fn switch(&self, preset_path: &Path, context: &ReshadeContext) -> io::Result<()> {
+        let key_combination = context
+            .preset_shortcuts
+            .get_by_right(preset_path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no keybind for preset"))?;
+        trigger_key_combination(key_combination);
+        Ok(())
+    }
+}
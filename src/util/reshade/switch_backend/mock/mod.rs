@@ -0,0 +1,42 @@
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_backend::{SwitchBackend, SwitchBackendKind};
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub type SwitchLog = Rc<RefCell<Vec<(SwitchBackendKind, PathBuf)>>>;
+
+// Records switches instead of performing them, shared between mocks to check which one switched.
+pub struct MockBackend {
+    pub kind: SwitchBackendKind,
+    pub can_switch: bool,
+    pub switched: SwitchLog,
+}
+
+impl MockBackend {
+    pub fn new(kind: SwitchBackendKind, can_switch: bool, switched: &SwitchLog) -> MockBackend {
+        MockBackend {
+            kind,
+            can_switch,
+            switched: switched.clone(),
+        }
+    }
+}
+
+impl SwitchBackend for MockBackend {
+    fn kind(&self) -> SwitchBackendKind {
+        self.kind
+    }
+
+    fn can_switch(&self, _preset_path: &Path, _context: &ReshadeContext) -> bool {
+        self.can_switch
+    }
+
+    fn switch(&self, preset_path: &Path, _context: &ReshadeContext) -> io::Result<()> {
+        self.switched
+            .borrow_mut()
+            .push((self.kind, preset_path.to_path_buf()));
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+pub mod dry_run;
+pub mod ini_write;
+pub mod ipc;
+pub mod keypress;
+#[cfg(test)]
+pub mod mock;
+
+use crate::config::ReshadeConfig;
+use crate::context::reshade_context::ReshadeContext;
+use crate::util::reshade::switch_backend::dry_run::DryRunBackend;
+use crate::util::reshade::switch_backend::ini_write::IniWriteBackend;
+use crate::util::reshade::switch_backend::ipc::IpcBackend;
+use crate::util::reshade::switch_backend::keypress::KeypressBackend;
+use function_name::named;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SwitchBackendKind {
+    #[default]
+    Keypress,
+    IniWrite,
+    Ipc,
+    DryRun,
+}
+
+impl SwitchBackendKind {
+    pub const ALL: [SwitchBackendKind; 4] = [
+        SwitchBackendKind::Keypress,
+        SwitchBackendKind::IniWrite,
+        SwitchBackendKind::Ipc,
+        SwitchBackendKind::DryRun,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SwitchBackendKind::Keypress => "Presses the preset keybind configured in ReShade.",
+            SwitchBackendKind::IniWrite => "Writes the preset to ReShade.ini and presses the ReShade reload key.\nWorks for presets without keybinds and applies custom transition durations.",
+            SwitchBackendKind::Ipc => "Asks a companion ReShade add-on to switch the preset.\nWorks for presets without keybinds and without game focus.",
+            SwitchBackendKind::DryRun => "Only logs which preset would be activated.",
+        }
+    }
+
+    // Keys are sent to whatever window has focus, so these only switch while the game has it.
+    pub fn needs_focus(&self) -> bool {
+        matches!(
+            self,
+            SwitchBackendKind::Keypress | SwitchBackendKind::IniWrite
+        )
+    }
+
+    // ReShade only reads the transition duration when reloading its configuration.
+    pub fn applies_transition_duration(&self) -> bool {
+        matches!(self, SwitchBackendKind::IniWrite)
+    }
+
+    pub fn backend(&self, reshade_config: &ReshadeConfig) -> Box<dyn SwitchBackend> {
+        match self {
+            SwitchBackendKind::Keypress => Box::new(KeypressBackend),
+            SwitchBackendKind::IniWrite => Box::new(IniWriteBackend::new(
+                reshade_config.ini_path.clone(),
+                reshade_config.original_transition_duration.clone(),
+            )),
+            SwitchBackendKind::Ipc => Box::new(IpcBackend),
+            SwitchBackendKind::DryRun => Box::new(DryRunBackend),
+        }
+    }
+}
+
+impl fmt::Display for SwitchBackendKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            SwitchBackendKind::Keypress => "Keybind",
+            SwitchBackendKind::IniWrite => "ReShade.ini and reload",
+            SwitchBackendKind::Ipc => "Companion add-on",
+            SwitchBackendKind::DryRun => "Dry run",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+pub trait SwitchBackend {
+    fn kind(&self) -> SwitchBackendKind;
+
+    fn can_switch(&self, preset_path: &Path, context: &ReshadeContext) -> bool;
+
+    fn switch(&self, preset_path: &Path, context: &ReshadeContext) -> io::Result<()>;
+}
+
+// The selected backend is tried first, the others take over for presets it cannot switch to.
+pub fn switch_backends(reshade_config: &ReshadeConfig) -> Vec<Box<dyn SwitchBackend>> {
+    let selected = reshade_config.switch_backend;
+    let fallbacks = [SwitchBackendKind::Keypress, SwitchBackendKind::IniWrite];
+    let mut kinds = vec![selected];
+    if selected != SwitchBackendKind::DryRun {
+        kinds.extend(fallbacks.into_iter().filter(|kind| *kind != selected));
+    }
+    kinds
+        .iter()
+        .map(|kind| kind.backend(reshade_config))
+        .collect()
+}
+
+// Backends sending keys are skipped while the game has no focus. Returns the backend that switched.
+#[named]
+pub fn switch_with_backends(
+    backends: &[Box<dyn SwitchBackend>],
+    preset_path: &Path,
+    context: &ReshadeContext,
+    has_focus: bool,
+) -> io::Result<SwitchBackendKind> {
+    let backend = backends
+        .iter()
+        .filter(|backend| has_focus || !backend.kind().needs_focus())
+        .find(|backend| backend.can_switch(preset_path, context))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no method can switch, assign a keybind or a reload key in ReShade",
+            )
+        })?;
+    if backends
+        .first()
+        .is_some_and(|selected| selected.kind() != backend.kind())
+    {
+        info!(
+            "[{}] Falling back to {} for preset [{}]",
+            function_name!(),
+            backend.kind(),
+            preset_path.display()
+        );
+    }
+    backend.switch(preset_path, context)?;
+    if context.transition_duration_ms.is_some() && !backend.kind().applies_transition_duration() {
+        warn!(
+            "[{}] {} cannot apply the transition duration of preset [{}]",
+            function_name!(),
+            backend.kind(),
+            preset_path.display()
+        );
+    }
+    Ok(backend.kind())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::reshade_context::key_combination::KeyCombination;
+    use crate::util::reshade::switch_backend::mock::{MockBackend, SwitchLog};
+    use crate::util::test_dir::TestDir;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn mocks(
+        backends: &[(SwitchBackendKind, bool)],
+        switched: &SwitchLog,
+    ) -> Vec<Box<dyn SwitchBackend>> {
+        backends
+            .iter()
+            .map(|&(kind, can_switch)| {
+                Box::new(MockBackend::new(kind, can_switch, switched)) as Box<dyn SwitchBackend>
+            })
+            .collect()
+    }
+
+    fn kinds(reshade_config: &ReshadeConfig) -> Vec<SwitchBackendKind> {
+        switch_backends(reshade_config)
+            .iter()
+            .map(|backend| backend.kind())
+            .collect()
+    }
+
+    fn key_combination(key_code: &str) -> KeyCombination {
+        KeyCombination {
+            key_code: key_code.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn selected_backend_comes_first() {
+        let mut reshade_config = ReshadeConfig::default();
+        assert_eq!(
+            kinds(&reshade_config),
+            [SwitchBackendKind::Keypress, SwitchBackendKind::IniWrite]
+        );
+        reshade_config.switch_backend = SwitchBackendKind::Ipc;
+        assert_eq!(
+            kinds(&reshade_config),
+            [
+                SwitchBackendKind::Ipc,
+                SwitchBackendKind::Keypress,
+                SwitchBackendKind::IniWrite
+            ]
+        );
+        reshade_config.switch_backend = SwitchBackendKind::DryRun;
+        assert_eq!(kinds(&reshade_config), [SwitchBackendKind::DryRun]);
+    }
+
+    #[test]
+    fn selected_backend_is_used() {
+        let switched = SwitchLog::default();
+        let backends = mocks(
+            &[
+                (SwitchBackendKind::Ipc, true),
+                (SwitchBackendKind::Keypress, true),
+            ],
+            &switched,
+        );
+        let preset_path = Path::new("Day.ini");
+        let kind =
+            switch_with_backends(&backends, preset_path, &ReshadeContext::default(), true).unwrap();
+        assert_eq!(kind, SwitchBackendKind::Ipc);
+        assert_eq!(
+            *switched.borrow(),
+            [(SwitchBackendKind::Ipc, preset_path.to_path_buf())]
+        );
+    }
+
+    #[test]
+    fn falls_back_when_selected_backend_cannot_switch() {
+        let switched = SwitchLog::default();
+        let backends = mocks(
+            &[
+                (SwitchBackendKind::Keypress, false),
+                (SwitchBackendKind::IniWrite, true),
+            ],
+            &switched,
+        );
+        let kind = switch_with_backends(
+            &backends,
+            Path::new("Day.ini"),
+            &ReshadeContext::default(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(kind, SwitchBackendKind::IniWrite);
+        assert_eq!(switched.borrow().len(), 1);
+    }
+
+    #[test]
+    fn skips_key_based_backends_without_focus() {
+        let switched = SwitchLog::default();
+        let backends = mocks(
+            &[
+                (SwitchBackendKind::Keypress, true),
+                (SwitchBackendKind::Ipc, true),
+            ],
+            &switched,
+        );
+        let context = ReshadeContext::default();
+        let kind = switch_with_backends(&backends, Path::new("Day.ini"), &context, false).unwrap();
+        assert_eq!(kind, SwitchBackendKind::Ipc);
+
+        let backends = mocks(&[(SwitchBackendKind::Keypress, true)], &switched);
+        assert!(switch_with_backends(&backends, Path::new("Day.ini"), &context, false).is_err());
+        assert_eq!(switched.borrow().len(), 1);
+    }
+
+    #[test]
+    fn errors_when_no_backend_can_switch() {
+        let switched = SwitchLog::default();
+        let backends = mocks(
+            &[
+                (SwitchBackendKind::Ipc, false),
+                (SwitchBackendKind::Keypress, false),
+                (SwitchBackendKind::IniWrite, false),
+            ],
+            &switched,
+        );
+        let error = switch_with_backends(
+            &backends,
+            Path::new("Day.ini"),
+            &ReshadeContext::default(),
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(switched.borrow().is_empty());
+    }
+
+    // Only checks which backend would switch, as switching sends keys.
+    #[test]
+    fn presets_without_keybind_fall_back_to_ini_write() {
+        let test_dir = TestDir::new("switch_backend_fallback");
+        let dir = test_dir.path();
+        let ini_path = dir.join("ReShade.ini");
+        let day: PathBuf = dir.join("Day.ini");
+        let night: PathBuf = dir.join("Night.ini");
+        for path in [&ini_path, &day, &night] {
+            fs::write(path, "").unwrap();
+        }
+        let reshade_config = ReshadeConfig {
+            ini_path,
+            ..Default::default()
+        };
+        let mut context = ReshadeContext::default();
+        context
+            .preset_shortcuts
+            .insert(key_combination("124"), day.clone());
+        let backends = switch_backends(&reshade_config);
+        let switching_kind = |preset_path: &Path, context: &ReshadeContext| {
+            backends
+                .iter()
+                .find(|backend| backend.can_switch(preset_path, context))
+                .map(|backend| backend.kind())
+        };
+
+        assert_eq!(
+            switching_kind(&day, &context),
+            Some(SwitchBackendKind::Keypress)
+        );
+        assert_eq!(switching_kind(&night, &context), None);
+        context.reload_key = Some(key_combination("116"));
+        assert_eq!(
+            switching_kind(&night, &context),
+            Some(SwitchBackendKind::IniWrite)
+        );
+    }
+}
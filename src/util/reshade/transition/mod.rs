@@ -0,0 +1,137 @@
+use crate::addon::Addon;
+use crate::config::ReshadeConfig;
+use crate::util::ini::Ini;
+use crate::util::reshade::GENERAL_SECTION;
+use function_name::named;
+use log::{info, warn};
+
+pub const PRESET_TRANSITION_DURATION: &str = "PresetTransitionDuration";
+
+// The value configured in ReShade is recorded before any rule overrides it and kept in the
+// addon config, so it can still be restored after the game crashed. Empty when ReShade.ini had
+// no value, the key is removed again on restore. Returns whether a value was recorded.
+#[named]
+pub fn record_original_transition_duration(reshade_config: &mut ReshadeConfig) -> bool {
+    if reshade_config.original_transition_duration.is_some() {
+        return false;
+    }
+    match Ini::read(&reshade_config.ini_path) {
+        Ok(reshade_ini) => {
+            let original_value = reshade_ini
+                .get(GENERAL_SECTION, PRESET_TRANSITION_DURATION)
+                .unwrap_or_default()
+                .to_string();
+            info!(
+                "[{}] Recorded preset transition duration [{}]",
+                function_name!(),
+                original_value
+            );
+            reshade_config.original_transition_duration = Some(original_value);
+            true
+        }
+        Err(e) => {
+            warn!(
+                "[{}] Could not record preset transition duration: {}",
+                function_name!(),
+                e
+            );
+            false
+        }
+    }
+}
+
+// ReShade only reads the duration when it loads its configuration, so it is written together
+// with the preset path right before the reload. Without a recorded value ReShade.ini is left as is.
+pub fn set_transition_duration(
+    reshade_ini: &mut Ini,
+    transition_duration_ms: Option<u32>,
+    original_value: Option<&str>,
+) {
+    let Some(original_value) = original_value else {
+        return;
+    };
+    match transition_duration_ms {
+        Some(transition_duration_ms) => reshade_ini.set(
+            GENERAL_SECTION,
+            PRESET_TRANSITION_DURATION,
+            &transition_duration_ms.to_string(),
+        ),
+        None => set_original_value(reshade_ini, original_value),
+    }
+}
+
+#[named]
+pub fn restore_transition_duration() {
+    let (ini_path, original_value) = {
+        let addon = Addon::lock();
+        (
+            addon.config.reshade.ini_path.clone(),
+            addon.config.reshade.original_transition_duration.clone(),
+        )
+    };
+    let Some(original_value) = original_value else {
+        return;
+    };
+    let result = Ini::read(&ini_path).and_then(|mut reshade_ini| {
+        set_original_value(&mut reshade_ini, &original_value);
+        reshade_ini.write(&ini_path)
+    });
+    match result {
+        Ok(()) => {
+            info!("[{}] Preset transition duration restored", function_name!());
+            let mut addon = Addon::lock();
+            addon.config.reshade.original_transition_duration = None;
+            addon.config.save();
+        }
+        Err(e) => warn!(
+            "[{}] Could not restore preset transition duration: {}",
+            function_name!(),
+            e
+        ),
+    }
+}
+
+fn set_original_value(reshade_ini: &mut Ini, original_value: &str) {
+    if original_value.is_empty() {
+        reshade_ini.remove(GENERAL_SECTION, PRESET_TRANSITION_DURATION);
+    } else {
+        reshade_ini.set(GENERAL_SECTION, PRESET_TRANSITION_DURATION, original_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESHADE_INI: &str =
+        "[GENERAL]\r\nPresetPath=.\\Day.ini\r\nPresetTransitionDuration=1000\r\n";
+
+    fn duration(reshade_ini: &Ini) -> Option<&str> {
+        reshade_ini.get(GENERAL_SECTION, PRESET_TRANSITION_DURATION)
+    }
+
+    #[test]
+    fn overrides_and_restores_recorded_value() {
+        let mut reshade_ini = Ini::parse(RESHADE_INI);
+        set_transition_duration(&mut reshade_ini, Some(250), Some("1000"));
+        assert_eq!(duration(&reshade_ini), Some("250"));
+        set_transition_duration(&mut reshade_ini, None, Some("1000"));
+        assert_eq!(reshade_ini.to_string(), RESHADE_INI);
+    }
+
+    #[test]
+    fn removes_value_missing_before() {
+        let mut reshade_ini = Ini::parse("[GENERAL]\r\nPresetPath=.\\Day.ini\r\n");
+        set_transition_duration(&mut reshade_ini, Some(250), Some(""));
+        assert_eq!(duration(&reshade_ini), Some("250"));
+        set_transition_duration(&mut reshade_ini, None, Some(""));
+        assert_eq!(duration(&reshade_ini), None);
+    }
+
+    #[test]
+    fn keeps_value_without_recorded_original() {
+        let mut reshade_ini = Ini::parse(RESHADE_INI);
+        set_transition_duration(&mut reshade_ini, Some(250), None);
+        assert_eq!(reshade_ini.to_string(), RESHADE_INI);
+    }
+}